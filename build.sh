@@ -6,4 +6,4 @@ if [ $# -ne 1 ]; then
     exit 1
 fi
 
-cargo run $1 && clang "$1.ll" -o "$1.out"
+cargo run $1
@@ -5,26 +5,52 @@ use anyhow::Result;
 use crate::{
     ast::{AstNode, BinaryAstNode},
     compiler::NodeCompiler,
+    span::Span,
 };
 
+pub struct Negation {
+    pub value: Box<dyn AstNode>,
+    pub span: Span,
+}
+
 pub struct Addition {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct Subtraction {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct Multiplication {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct Division {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+pub struct Remainder {
+    pub left: Box<dyn AstNode>,
+    pub right: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+impl AstNode for Negation {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_negation(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for Addition {
@@ -32,7 +58,8 @@ impl BinaryAstNode for Addition {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -40,6 +67,10 @@ impl AstNode for Addition {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_sum(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for Subtraction {
@@ -47,7 +78,8 @@ impl BinaryAstNode for Subtraction {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -55,6 +87,10 @@ impl AstNode for Subtraction {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_subtraction(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for Multiplication {
@@ -62,7 +98,8 @@ impl BinaryAstNode for Multiplication {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -70,6 +107,10 @@ impl AstNode for Multiplication {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_multiplication(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for Division {
@@ -77,7 +118,8 @@ impl BinaryAstNode for Division {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -85,6 +127,36 @@ impl AstNode for Division {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_division(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl BinaryAstNode for Remainder {
+    fn new(left: Box<dyn AstNode>, right: Box<dyn AstNode>) -> Box<dyn BinaryAstNode>
+    where
+        Self: Sized,
+    {
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
+    }
+}
+
+impl AstNode for Remainder {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_remainder(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Display for Negation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Negation({})", self.value)
+    }
 }
 
 impl Display for Addition {
@@ -110,3 +182,9 @@ impl Display for Division {
         write!(f, "Division({}, {})", self.left, self.right)
     }
 }
+
+impl Display for Remainder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Remainder({}, {})", self.left, self.right)
+    }
+}
@@ -1,48 +1,72 @@
 use crate::{
     ast::{AstNode, BinaryAstNode},
     compiler::NodeCompiler,
+    span::Span,
 };
 use anyhow::Result;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 pub struct LogicalNot {
     pub value: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct LogicalOr {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct LogicalAnd {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+pub struct Equal {
+    pub left: Box<dyn AstNode>,
+    pub right: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+pub struct NotEqual {
+    pub left: Box<dyn AstNode>,
+    pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct Less {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct Greater {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct LessEqual {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct GreaterEqual {
     pub left: Box<dyn AstNode>,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 impl AstNode for LogicalNot {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_logical_not(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for LogicalOr {
@@ -50,7 +74,8 @@ impl BinaryAstNode for LogicalOr {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -58,6 +83,10 @@ impl AstNode for LogicalOr {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_logical_or(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for LogicalAnd {
@@ -65,7 +94,8 @@ impl BinaryAstNode for LogicalAnd {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -73,6 +103,50 @@ impl AstNode for LogicalAnd {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_logical_and(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl BinaryAstNode for Equal {
+    fn new(left: Box<dyn AstNode>, right: Box<dyn AstNode>) -> Box<dyn BinaryAstNode>
+    where
+        Self: Sized,
+    {
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
+    }
+}
+
+impl AstNode for Equal {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_equal(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl BinaryAstNode for NotEqual {
+    fn new(left: Box<dyn AstNode>, right: Box<dyn AstNode>) -> Box<dyn BinaryAstNode>
+    where
+        Self: Sized,
+    {
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
+    }
+}
+
+impl AstNode for NotEqual {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_not_equal(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for Less {
@@ -80,7 +154,8 @@ impl BinaryAstNode for Less {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -88,6 +163,10 @@ impl AstNode for Less {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_less(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for Greater {
@@ -95,7 +174,8 @@ impl BinaryAstNode for Greater {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -103,6 +183,10 @@ impl AstNode for Greater {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_greater(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for LessEqual {
@@ -110,7 +194,8 @@ impl BinaryAstNode for LessEqual {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -118,6 +203,10 @@ impl AstNode for LessEqual {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_less_equal(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl BinaryAstNode for GreaterEqual {
@@ -125,7 +214,8 @@ impl BinaryAstNode for GreaterEqual {
     where
         Self: Sized,
     {
-        Box::new(Self { left, right })
+        let span = left.span().to(right.span());
+        Box::new(Self { left, right, span })
     }
 }
 
@@ -133,6 +223,10 @@ impl AstNode for GreaterEqual {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_greater_equal(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl Display for LogicalNot {
@@ -153,6 +247,18 @@ impl Display for LogicalAnd {
     }
 }
 
+impl Display for Equal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Equal({}, {})", self.left, self.right)
+    }
+}
+
+impl Display for NotEqual {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "NotEqual({}, {})", self.left, self.right)
+    }
+}
+
 impl Display for Less {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "Less({}, {})", self.left, self.right)
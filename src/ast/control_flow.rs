@@ -1,23 +1,49 @@
 use crate::{
-    ast::{AstNode, GenericAstNode},
+    ast::{AstNode, GenericAstNode, lvalues::Identifier},
     compiler::NodeCompiler,
+    span::Span,
 };
 use anyhow::Result;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 pub struct Block {
-    nodes: Vec<Box<dyn AstNode>>,
+    pub nodes: Vec<Box<dyn AstNode>>,
+    pub span: Span,
 }
 
 pub struct If {
-    expression: Box<dyn AstNode>,
-    then: Box<dyn AstNode>,
-    otherwise: Option<Box<dyn AstNode>>,
+    pub expression: Box<dyn AstNode>,
+    pub then: Box<dyn AstNode>,
+    pub otherwise: Option<Box<dyn AstNode>>,
+    pub span: Span,
 }
 
 pub struct While {
-    expression: Box<dyn AstNode>,
-    block: Box<dyn AstNode>,
+    pub expression: Box<dyn AstNode>,
+    pub block: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+/// `for variable in start..end block`, or `..=` to include `end`. Both bounds are
+/// evaluated once, before the first iteration, and `variable` only exists inside
+/// `block`.
+pub struct For {
+    pub variable: Identifier,
+    pub start: Box<dyn AstNode>,
+    pub end: Box<dyn AstNode>,
+    pub inclusive: bool,
+    pub block: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+/// `break;`, leaving the innermost loop.
+pub struct Break {
+    pub span: Span,
+}
+
+/// `continue;`, skipping to the next iteration of the innermost loop.
+pub struct Continue {
+    pub span: Span,
 }
 
 impl If {
@@ -25,58 +51,105 @@ impl If {
         expression: Box<dyn AstNode>,
         then: Box<dyn AstNode>,
         otherwise: Option<Box<dyn AstNode>>,
+        span: Span,
     ) -> Box<dyn AstNode> {
         Box::new(Self {
             expression,
             then,
             otherwise,
+            span,
         })
     }
 }
 
 impl While {
-    pub fn new(expression: Box<dyn AstNode>, block: Box<dyn AstNode>) -> Box<dyn AstNode> {
-        Box::new(Self { expression, block })
+    pub fn new(
+        expression: Box<dyn AstNode>,
+        block: Box<dyn AstNode>,
+        span: Span,
+    ) -> Box<dyn AstNode> {
+        Box::new(Self {
+            expression,
+            block,
+            span,
+        })
     }
 }
 
 impl GenericAstNode for Block {
-    fn new() -> Box<dyn GenericAstNode> {
-        Box::new(Self { nodes: Vec::new() })
+    fn new(span: Span) -> Box<dyn GenericAstNode> {
+        Box::new(Self {
+            nodes: Vec::new(),
+            span,
+        })
     }
 
     fn add_node(&mut self, node: Box<dyn AstNode>) {
         self.nodes.push(node);
     }
+
+    fn set_span(&mut self, span: Span) {
+        self.span = span;
+    }
 }
 
 impl AstNode for Block {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
-        for node in &self.nodes {
-            node.accept(visitor)?;
-        }
-        Ok(())
+        visitor.compile_block(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
     }
 }
 
 impl AstNode for If {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
-        self.expression.accept(visitor)?;
-        self.then.accept(visitor)?;
-        if let Some(expression) = &self.otherwise {
-            expression.accept(visitor)?;
-        }
+        visitor.compile_if(self)
+    }
 
-        Ok(())
+    fn span(&self) -> &Span {
+        &self.span
     }
 }
 
 impl AstNode for While {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
-        self.expression.accept(visitor)?;
-        self.block.accept(visitor)?;
+        visitor.compile_while(self)
+    }
 
-        Ok(())
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AstNode for For {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_for(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AstNode for Break {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_break(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AstNode for Continue {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_continue(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
     }
 }
 
@@ -108,3 +181,30 @@ impl Display for While {
         write!(f, "While({}, {})", self.expression, self.block)
     }
 }
+
+impl Display for For {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let range = if self.inclusive {
+            "RangeInclusive"
+        } else {
+            "Range"
+        };
+        write!(
+            f,
+            "For({}, {}({}, {}), {})",
+            self.variable, range, self.start, self.end, self.block
+        )
+    }
+}
+
+impl Display for Break {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Break")
+    }
+}
+
+impl Display for Continue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Continue")
+    }
+}
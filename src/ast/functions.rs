@@ -0,0 +1,89 @@
+use crate::{
+    ast::{AstNode, lvalues::Identifier},
+    compiler::NodeCompiler,
+    span::Span,
+};
+use anyhow::Result;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// `fn name(parameters) -> i32 body`; every parameter is an `i32`.
+pub struct Function {
+    pub name: Identifier,
+    pub parameters: Vec<Identifier>,
+    pub body: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+pub struct Call {
+    pub name: Identifier,
+    pub arguments: Vec<Box<dyn AstNode>>,
+    pub span: Span,
+}
+
+pub struct Return {
+    pub value: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+impl AstNode for Function {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_function(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AstNode for Call {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_call(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AstNode for Return {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_return(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+/// Writes `items` separated by commas.
+pub(super) fn write_list<T: Display>(f: &mut Formatter<'_>, items: &[T]) -> FmtResult {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Function({}, [", self.name)?;
+        write_list(f, &self.parameters)?;
+        write!(f, "], {})", self.body)
+    }
+}
+
+impl Display for Call {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Call({}, [", self.name)?;
+        write_list(f, &self.arguments)?;
+        write!(f, "])")
+    }
+}
+
+impl Display for Return {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Return({})", self.value)
+    }
+}
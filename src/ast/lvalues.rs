@@ -1,25 +1,49 @@
-use crate::{
-    ast::{AstNode, BinaryAstNode},
-    compiler::NodeCompiler,
-};
+use crate::{ast::AstNode, compiler::NodeCompiler, span::Span};
 use anyhow::Result;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 pub struct Identifier {
     pub name: String,
+    pub span: Span,
+}
+
+pub struct Declaration {
+    pub left: Identifier,
+    pub right: Box<dyn AstNode>,
+    pub span: Span,
 }
 
 pub struct Assignment {
-    pub left: Box<dyn AstNode>,
+    pub left: Identifier,
     pub right: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompoundOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
 }
 
-impl BinaryAstNode for Assignment {
-    fn new(left: Box<dyn AstNode>, right: Box<dyn AstNode>) -> Box<dyn BinaryAstNode>
-    where
-        Self: Sized,
-    {
-        Box::new(Self { left, right })
+/// `left <operator>= right`, kept as a single node so the target is only
+/// evaluated once.
+pub struct CompoundAssignment {
+    pub left: Identifier,
+    pub operator: CompoundOperator,
+    pub right: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+impl AstNode for Declaration {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_declaration(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
     }
 }
 
@@ -27,12 +51,36 @@ impl AstNode for Assignment {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_assignment(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AstNode for CompoundAssignment {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_compound_assignment(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl AstNode for Identifier {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_identifier(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Display for Declaration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Declaration({}, {})", self.left, self.right)
+    }
 }
 
 impl Display for Assignment {
@@ -41,6 +89,29 @@ impl Display for Assignment {
     }
 }
 
+impl Display for CompoundOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let operator = match self {
+            Self::Add => "+=",
+            Self::Subtract => "-=",
+            Self::Multiply => "*=",
+            Self::Divide => "/=",
+            Self::Remainder => "%=",
+        };
+        write!(f, "{}", operator)
+    }
+}
+
+impl Display for CompoundAssignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "CompoundAssignment({}, {}, {})",
+            self.left, self.operator, self.right
+        )
+    }
+}
+
 impl Display for Identifier {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "{}", self.name)
@@ -1,16 +1,19 @@
 pub mod arithmetic;
 pub mod boolean;
 pub mod control_flow;
+pub mod functions;
 pub mod lvalues;
+pub mod program;
 pub mod rvalues;
 
 use std::fmt::Display;
 
-use crate::compiler::NodeCompiler;
+use crate::{compiler::NodeCompiler, span::Span};
 use anyhow::Result;
 
 pub trait AstNode: Display {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()>;
+    fn span(&self) -> &Span;
 }
 
 pub trait BinaryAstNode: AstNode {
@@ -20,8 +23,9 @@ pub trait BinaryAstNode: AstNode {
 }
 
 pub trait GenericAstNode: AstNode {
-    fn new() -> Box<dyn GenericAstNode>
+    fn new(span: Span) -> Box<dyn GenericAstNode>
     where
         Self: Sized;
     fn add_node(&mut self, node: Box<dyn AstNode>);
+    fn set_span(&mut self, span: Span);
 }
@@ -0,0 +1,85 @@
+use crate::{
+    ast::{
+        AstNode,
+        functions::{Function, write_list},
+        lvalues::{Declaration, Identifier},
+    },
+    compiler::NodeCompiler,
+    span::Span,
+};
+use anyhow::Result;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// The root of a source file: its items, in the order they appear.
+pub struct Program {
+    pub items: Vec<Item>,
+    pub span: Span,
+}
+
+pub enum Item {
+    Function(Function),
+    /// A `let` outside of any function, visible to all of them.
+    Global(Declaration),
+    Constant(Constant),
+}
+
+/// `const NAME: i32 = value;`, where `value` must be known at compile time.
+pub struct Constant {
+    pub name: Identifier,
+    pub value: Box<dyn AstNode>,
+    pub span: Span,
+}
+
+impl Item {
+    pub fn name(&self) -> &Identifier {
+        match self {
+            Item::Function(function) => &function.name,
+            Item::Global(global) => &global.left,
+            Item::Constant(constant) => &constant.name,
+        }
+    }
+}
+
+impl AstNode for Program {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_program(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AstNode for Constant {
+    fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
+        visitor.compile_constant(self)
+    }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Program(")?;
+        write_list(f, &self.items)?;
+        write!(f, ")")
+    }
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Item::Function(function) => write!(f, "{}", function),
+            Item::Global(global) => write!(f, "Global({}, {})", global.left, global.right),
+            Item::Constant(constant) => write!(f, "{}", constant),
+        }
+    }
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Constant({}, {})", self.name, self.value)
+    }
+}
@@ -1,18 +1,19 @@
-use crate::{ast::AstNode, compiler::NodeCompiler};
+use crate::{ast::AstNode, compiler::NodeCompiler, span::Span};
 use anyhow::Result;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 pub trait RValue: AstNode {
-    fn default() -> Box<dyn AstNode>;
+    fn default(span: Span) -> Box<dyn AstNode>;
 }
 
 pub struct Integer {
     pub value: i32,
+    pub span: Span,
 }
 
 impl RValue for Integer {
-    fn default() -> Box<dyn AstNode> {
-        Box::new(Self { value: 0 })
+    fn default(span: Span) -> Box<dyn AstNode> {
+        Box::new(Self { value: 0, span })
     }
 }
 
@@ -20,6 +21,10 @@ impl AstNode for Integer {
     fn accept(&self, visitor: &mut dyn NodeCompiler) -> Result<()> {
         visitor.compile_int_lit(self)
     }
+
+    fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl Display for Integer {
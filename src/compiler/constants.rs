@@ -0,0 +1,291 @@
+use crate::{
+    ast::{
+        AstNode, arithmetic::*, boolean::*, control_flow::*, functions::*, lvalues::*, program::*,
+        rvalues::*,
+    },
+    compiler::NodeCompiler,
+    diagnostics::Diagnostic,
+    span::Span,
+};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+
+/// Evaluates the initializers of constants and globals at compile time, with the same
+/// wrapping `i32` arithmetic the generated code uses. Only literals, operators and
+/// previously defined constants are allowed.
+pub struct ConstantEvaluator<'a> {
+    constants: &'a HashMap<String, i32>,
+    values: Vec<i32>,
+}
+
+impl<'a> ConstantEvaluator<'a> {
+    pub fn new(constants: &'a HashMap<String, i32>) -> Self {
+        ConstantEvaluator {
+            constants,
+            values: Vec::new(),
+        }
+    }
+
+    /// Computes the value of `node`, which may refer to any of `constants`.
+    pub fn evaluate(node: &dyn AstNode, constants: &HashMap<String, i32>) -> Result<i32> {
+        let mut evaluator = ConstantEvaluator::new(constants);
+        node.accept(&mut evaluator)?;
+        evaluator.value()
+    }
+
+    fn value(&mut self) -> Result<i32> {
+        self.values
+            .pop()
+            .ok_or_else(|| anyhow!("Operand not found in constant expression"))
+    }
+
+    fn unary(&mut self, value: &dyn AstNode, operator: impl Fn(i32) -> i32) -> Result<()> {
+        value.accept(self)?;
+        let value = self.value()?;
+        self.values.push(operator(value));
+
+        Ok(())
+    }
+
+    fn binary(
+        &mut self,
+        left: &dyn AstNode,
+        right: &dyn AstNode,
+        operator: impl Fn(i32, i32) -> Option<i32>,
+        span: &Span,
+    ) -> Result<()> {
+        left.accept(self)?;
+        right.accept(self)?;
+        let right = self.value()?;
+        let left = self.value()?;
+
+        let value = operator(left, right).ok_or_else(|| {
+            Diagnostic::new(
+                "constant expression divides by zero or overflows",
+                span.clone(),
+            )
+        })?;
+        self.values.push(value);
+
+        Ok(())
+    }
+
+    /// Evaluates `&&` and `||` with the same short-circuiting as the generated code.
+    fn short_circuit(
+        &mut self,
+        left: &dyn AstNode,
+        right: &dyn AstNode,
+        short_circuit_value: bool,
+    ) -> Result<()> {
+        left.accept(self)?;
+        let value = if (self.value()? != 0) == short_circuit_value {
+            short_circuit_value
+        } else {
+            right.accept(self)?;
+            self.value()? != 0
+        };
+        self.values.push(value as i32);
+
+        Ok(())
+    }
+}
+
+fn not_constant(span: &Span) -> anyhow::Error {
+    Diagnostic::new("expected a constant expression", span.clone()).into()
+}
+
+impl NodeCompiler for ConstantEvaluator<'_> {
+    /// The compiled form of a constant expression is its value.
+    fn compile(&mut self) -> Result<String> {
+        Ok(self.value()?.to_string())
+    }
+
+    fn compile_program(&mut self, node: &Program) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_constant(&mut self, node: &Constant) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_negation(&mut self, node: &Negation) -> Result<()> {
+        self.unary(node.value.as_ref(), i32::wrapping_neg)
+    }
+
+    fn compile_sum(&mut self, node: &Addition) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some(a.wrapping_add(b)), &node.span)
+    }
+
+    fn compile_subtraction(&mut self, node: &Subtraction) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some(a.wrapping_sub(b)), &node.span)
+    }
+
+    fn compile_multiplication(&mut self, node: &Multiplication) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some(a.wrapping_mul(b)), &node.span)
+    }
+
+    fn compile_division(&mut self, node: &Division) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, i32::checked_div, &node.span)
+    }
+
+    fn compile_remainder(&mut self, node: &Remainder) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, i32::checked_rem, &node.span)
+    }
+
+    fn compile_logical_not(&mut self, node: &LogicalNot) -> Result<()> {
+        self.unary(node.value.as_ref(), |value| (value == 0) as i32)
+    }
+
+    fn compile_logical_or(&mut self, node: &LogicalOr) -> Result<()> {
+        self.short_circuit(node.left.as_ref(), node.right.as_ref(), true)
+    }
+
+    fn compile_logical_and(&mut self, node: &LogicalAnd) -> Result<()> {
+        self.short_circuit(node.left.as_ref(), node.right.as_ref(), false)
+    }
+
+    fn compile_equal(&mut self, node: &Equal) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some((a == b) as i32), &node.span)
+    }
+
+    fn compile_not_equal(&mut self, node: &NotEqual) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some((a != b) as i32), &node.span)
+    }
+
+    fn compile_less(&mut self, node: &Less) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some((a < b) as i32), &node.span)
+    }
+
+    fn compile_greater(&mut self, node: &Greater) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some((a > b) as i32), &node.span)
+    }
+
+    fn compile_less_equal(&mut self, node: &LessEqual) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some((a <= b) as i32), &node.span)
+    }
+
+    fn compile_greater_equal(&mut self, node: &GreaterEqual) -> Result<()> {
+        let (left, right) = (node.left.as_ref(), node.right.as_ref());
+        self.binary(left, right, |a, b| Some((a >= b) as i32), &node.span)
+    }
+
+    fn compile_block(&mut self, node: &Block) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_if(&mut self, node: &If) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_while(&mut self, node: &While) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_for(&mut self, node: &For) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_break(&mut self, node: &Break) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_continue(&mut self, node: &Continue) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_function(&mut self, node: &Function) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_call(&mut self, node: &Call) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_return(&mut self, node: &Return) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_declaration(&mut self, node: &Declaration) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_assignment(&mut self, node: &Assignment) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_compound_assignment(&mut self, node: &CompoundAssignment) -> Result<()> {
+        Err(not_constant(&node.span))
+    }
+
+    fn compile_identifier(&mut self, node: &Identifier) -> Result<()> {
+        let value = self.constants.get(&node.name).ok_or_else(|| {
+            Diagnostic::new(
+                format!("`{}` is not a constant", node.name),
+                node.span.clone(),
+            )
+        })?;
+        self.values.push(*value);
+
+        Ok(())
+    }
+
+    fn compile_int_lit(&mut self, node: &Integer) -> Result<()> {
+        self.values.push(node.value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConstantEvaluator;
+    use crate::{
+        lexer::Lexer,
+        parser::{Or, Parser},
+    };
+    use std::collections::HashMap;
+
+    fn evaluate(source: &str) -> Result<i32, String> {
+        let tokens = Lexer::new("test", source).tokenize();
+        let (node, _) = Or::parse(tokens.stream()).unwrap();
+
+        let constants = HashMap::from([("N".to_owned(), 10)]);
+        ConstantEvaluator::evaluate(node.as_ref(), &constants).map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("N * 2 + -3 % 2"), Ok(19));
+        assert_eq!(evaluate("(N > 5) + (N == 10) + !N"), Ok(2));
+        assert_eq!(evaluate("2147483647 + 1"), Ok(i32::MIN));
+        // The right operand is never evaluated, so it cannot fail.
+        assert_eq!(evaluate("0 && 1 / 0"), Ok(0));
+        assert_eq!(evaluate("N || 1 / 0"), Ok(1));
+    }
+
+    #[test]
+    fn test_not_constant() {
+        assert_eq!(
+            evaluate("x + 1"),
+            Err("test:1:1: `x` is not a constant".into())
+        );
+        assert_eq!(
+            evaluate("f(1)"),
+            Err("test:1:1: expected a constant expression".into())
+        );
+        assert_eq!(
+            evaluate("N / (N - 10)"),
+            Err("test:1:1: constant expression divides by zero or overflows".into())
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::span::Span;
+use anyhow::{Result, anyhow};
+use inkwell::{
+    basic_block::BasicBlock,
+    builder::Builder,
+    context::Context,
+    debug_info::{
+        AsDIScope, DIBasicType, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DIScope,
+        DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder, debug_metadata_version,
+    },
+    module::{FlagBehavior, Linkage, Module},
+    values::{FunctionValue, PointerValue},
+};
+use std::path::Path;
+
+/// `DW_ATE_signed` from the DWARF specification.
+const DW_ATE_SIGNED: u32 = 0x05;
+
+/// DWARF debug information for a module: one compile unit for the source file, a
+/// subprogram per function and a descriptor per local variable.
+pub struct DebugInfo<'a> {
+    builder: DebugInfoBuilder<'a>,
+    compile_unit: DICompileUnit<'a>,
+    file: DIFile<'a>,
+    i32_type: DIBasicType<'a>,
+    /// Scopes of the functions being compiled, innermost last.
+    scopes: Vec<DIScope<'a>>,
+}
+
+impl<'a> DebugInfo<'a> {
+    pub fn new(
+        context: &'a Context,
+        module: &Module<'a>,
+        path: &Path,
+        optimized: bool,
+    ) -> Result<Self> {
+        module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            context
+                .i32_type()
+                .const_int(debug_metadata_version() as u64, false),
+        );
+        module.add_basic_value_flag(
+            "Dwarf Version",
+            FlagBehavior::Warning,
+            context.i32_type().const_int(4, false),
+        );
+
+        let filename = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("Invalid source file name `{}`", path.display()))?;
+        let directory = match path.parent().and_then(|parent| parent.to_str()) {
+            Some("") | None => ".",
+            Some(parent) => parent,
+        };
+
+        let (builder, compile_unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            filename,
+            directory,
+            env!("CARGO_PKG_NAME"),
+            optimized,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        let i32_type = builder
+            .create_basic_type("i32", 32, DW_ATE_SIGNED, DIFlags::ZERO)
+            .map_err(|message| anyhow!("Failed to describe `i32`: {}", message))?;
+
+        Ok(Self {
+            file: compile_unit.get_file(),
+            scopes: vec![compile_unit.as_debug_info_scope()],
+            builder,
+            compile_unit,
+            i32_type,
+        })
+    }
+
+    /// Describes `function`, defined at `line`, and makes it the scope of the locations
+    /// and variables that follow until `leave_function`.
+    pub fn add_function(&mut self, function: FunctionValue<'a>, name: &str, line: u32) {
+        let parameters = vec![self.i32_type.as_type(); function.count_params() as usize];
+        let subroutine_type = self.builder.create_subroutine_type(
+            self.file,
+            Some(self.i32_type.as_type()),
+            &parameters,
+            DIFlags::ZERO,
+        );
+        let subprogram = self.builder.create_function(
+            self.compile_unit.as_debug_info_scope(),
+            name,
+            None,
+            self.file,
+            line,
+            subroutine_type,
+            function.get_linkage() == Linkage::Internal,
+            true,
+            line,
+            DIFlags::ZERO,
+            false,
+        );
+
+        function.set_subprogram(subprogram);
+        self.scopes.push(subprogram.as_debug_info_scope());
+    }
+
+    /// Returns to the scope of the function enclosing the one just compiled.
+    pub fn leave_function(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn scope(&self) -> DIScope<'a> {
+        *self.scopes.last().unwrap()
+    }
+
+    /// Attributes the instructions built from now on to the start of `span`.
+    pub fn set_location(&self, context: &'a Context, builder: &Builder<'a>, span: &Span) {
+        let location = self.builder.create_debug_location(
+            context,
+            span.start.line as u32,
+            span.start.column as u32,
+            self.scope(),
+            None,
+        );
+        builder.set_current_debug_location(location);
+    }
+
+    /// Describes the local variable `name`, declared at `span` and stored in `slot`.
+    pub fn declare_variable(
+        &self,
+        context: &'a Context,
+        name: &str,
+        slot: PointerValue<'a>,
+        span: &Span,
+        block: BasicBlock<'a>,
+    ) {
+        let variable = self.builder.create_auto_variable(
+            self.scope(),
+            name,
+            self.file,
+            span.start.line as u32,
+            self.i32_type.as_type(),
+            true,
+            DIFlags::ZERO,
+            32,
+        );
+        let location = self.builder.create_debug_location(
+            context,
+            span.start.line as u32,
+            span.start.column as u32,
+            self.scope(),
+            None,
+        );
+        self.builder
+            .insert_declare_at_end(slot, Some(variable), None, location, block);
+    }
+
+    /// Resolves the debug information built so far; must run before the module is verified.
+    pub fn finalize(&self) {
+        self.builder.finalize();
+    }
+}
@@ -1,10 +1,27 @@
 use crate::{
-    ast::{arithmetic::*, boolean::*, lvalues::*, rvalues::*},
-    compiler::NodeCompiler,
+    ast::{
+        AstNode, arithmetic::*, boolean::*, control_flow::*, functions::*, lvalues::*, program::*,
+        rvalues::*,
+    },
+    compiler::{NodeCompiler, constants::ConstantEvaluator, debuginfo::DebugInfo},
+    diagnostics::Diagnostic,
+    span::Span,
 };
 use anyhow::Result;
 use inkwell::{
-    basic_block::BasicBlock, builder::Builder, context::Context, module::Module, values::IntValue,
+    IntPredicate, OptimizationLevel,
+    basic_block::BasicBlock,
+    builder::Builder,
+    context::Context,
+    module::{Linkage, Module},
+    passes::PassBuilderOptions,
+    targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine},
+    types::BasicMetadataTypeEnum,
+    values::{AnyValue, BasicMetadataValueEnum, FunctionValue, IntValue, PointerValue},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
 };
 
 pub struct LlvmCompiler<'a> {
@@ -14,6 +31,28 @@ pub struct LlvmCompiler<'a> {
 
     intermediate_values: Vec<IntValue<'a>>,
     blocks: Vec<BasicBlock<'a>>,
+    /// Stack slots of the variables in scope, innermost block last.
+    scopes: Vec<HashMap<String, PointerValue<'a>>>,
+    /// Global variables, visible in every function unless shadowed.
+    globals: HashMap<String, PointerValue<'a>>,
+    /// Values of the constants, which are inlined wherever they are used.
+    constants: HashMap<String, i32>,
+    /// Branch targets of the loops being compiled, innermost last.
+    loops: Vec<LoopTargets<'a>>,
+    debug_info: Option<DebugInfo<'a>>,
+}
+
+/// Where `break` and `continue` branch to inside a loop.
+struct LoopTargets<'a> {
+    break_block: BasicBlock<'a>,
+    continue_block: BasicBlock<'a>,
+}
+
+/// What a name in scope refers to.
+enum Symbol<'a> {
+    /// A local or global variable, stored at the pointer.
+    Variable(PointerValue<'a>),
+    Constant(i32),
 }
 
 impl<'a> LlvmCompiler<'a> {
@@ -21,31 +60,464 @@ impl<'a> LlvmCompiler<'a> {
         let builder = context.create_builder();
         let module = context.create_module(program_name);
 
-        let i32_type = context.i32_type();
-        let fn_type = i32_type.fn_type(&[], false);
-        let function = module.add_function("main", fn_type, None);
-        let main_block = context.append_basic_block(function, "entry");
-
         LlvmCompiler {
             context,
             builder,
             module,
             intermediate_values: Vec::new(),
-            blocks: vec![main_block],
+            blocks: Vec::new(),
+            scopes: Vec::new(),
+            globals: HashMap::new(),
+            constants: HashMap::new(),
+            loops: Vec::new(),
+            debug_info: None,
+        }
+    }
+
+    /// Emits DWARF debug information for the program read from `path`. Must be called
+    /// before anything is compiled.
+    pub fn enable_debug_info(&mut self, path: &Path, optimized: bool) -> Result<()> {
+        self.debug_info = Some(DebugInfo::new(self.context, &self.module, path, optimized)?);
+        Ok(())
+    }
+
+    /// Runs LLVM's default optimization pipeline for `level` over the compiled module and
+    /// returns the resulting IR.
+    pub fn optimize(&self, level: OptimizationLevel) -> Result<String> {
+        let pipeline = match level {
+            OptimizationLevel::None => "default<O0>",
+            OptimizationLevel::Less => "default<O1>",
+            OptimizationLevel::Default => "default<O2>",
+            OptimizationLevel::Aggressive => "default<O3>",
+        };
+
+        let machine = self.target_machine(level)?;
+        self.module
+            .run_passes(pipeline, &machine, PassBuilderOptions::create())
+            .map_err(|message| anyhow::anyhow!("Failed to run `{}`: {}", pipeline, message))?;
+
+        Ok(self.module.print_to_string().to_string())
+    }
+
+    /// Writes the compiled module for the host machine, both as an object file ready to be
+    /// linked and as human-readable assembly.
+    pub fn emit(&self, object: &Path, assembly: &Path, level: OptimizationLevel) -> Result<()> {
+        let machine = self.target_machine(level)?;
+        machine
+            .write_to_file(&self.module, FileType::Object, object)
+            .map_err(|message| anyhow::anyhow!("Failed to write object file: {}", message))?;
+        machine
+            .write_to_file(&self.module, FileType::Assembly, assembly)
+            .map_err(|message| anyhow::anyhow!("Failed to write assembly: {}", message))?;
+
+        Ok(())
+    }
+
+    /// JIT-compiles the module in-process, calls its `main` function and returns the value
+    /// it produced.
+    pub fn run(&self, level: OptimizationLevel) -> Result<i32> {
+        type MainFunction = unsafe extern "C" fn() -> i32;
+
+        Target::initialize_native(&InitializationConfig::default()).map_err(|message| {
+            anyhow::anyhow!("Failed to initialize native target: {}", message)
+        })?;
+        let engine = self
+            .module
+            .create_jit_execution_engine(level)
+            .map_err(|message| anyhow::anyhow!("Failed to create JIT: {}", message))?;
+
+        // SAFETY: `compile_program` only accepts a `main` declared as `i32 ()`, matching `MainFunction`.
+        let main = unsafe { engine.get_function::<MainFunction>("main") }
+            .map_err(|error| anyhow::anyhow!("Failed to look up `main`: {}", error))?;
+
+        // SAFETY: the module passed verification in `compile`, and `main` takes no arguments.
+        Ok(unsafe { main.call() })
+    }
+
+    /// Creates a target machine for the host and sets the module's triple and data layout
+    /// to match it.
+    fn target_machine(&self, level: OptimizationLevel) -> Result<TargetMachine> {
+        Target::initialize_native(&InitializationConfig::default()).map_err(|message| {
+            anyhow::anyhow!("Failed to initialize native target: {}", message)
+        })?;
+
+        let triple = TargetMachine::get_default_triple();
+        let target = Target::from_triple(&triple)
+            .map_err(|message| anyhow::anyhow!("Unsupported target triple: {}", message))?;
+        let machine = target
+            .create_target_machine(
+                &triple,
+                &TargetMachine::get_host_cpu_name().to_string(),
+                &TargetMachine::get_host_cpu_features().to_string(),
+                level,
+                RelocMode::PIC,
+                CodeModel::Default,
+            )
+            .ok_or_else(|| anyhow::anyhow!("Failed to create a target machine for {}", triple))?;
+
+        self.module.set_triple(&triple);
+        self.module
+            .set_data_layout(&machine.get_target_data().get_data_layout());
+
+        Ok(machine)
+    }
+}
+
+impl<'a> LlvmCompiler<'a> {
+    /// Replaces the block on top of `blocks` and moves the builder to its end, keeping the
+    /// invariant that the top of the stack is the block currently being filled.
+    fn switch_to_block(&mut self, block: BasicBlock<'a>) {
+        self.blocks.pop();
+        self.blocks.push(block);
+        self.builder.position_at_end(block);
+    }
+
+    /// Returns the block currently being filled.
+    fn current_block(&self) -> Result<BasicBlock<'a>> {
+        self.blocks
+            .last()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("No basic block to insert instructions into"))
+    }
+
+    /// Returns the function the current block belongs to.
+    fn current_function(&self) -> Result<FunctionValue<'a>> {
+        self.current_block()?
+            .get_parent()
+            .ok_or_else(|| anyhow::anyhow!("Basic block is not part of a function"))
+    }
+
+    /// Continues in a new block that nothing branches to, for the code following a jump
+    /// like `return`. That code can never run, but still has to go somewhere for the IR to
+    /// stay valid.
+    fn start_dead_block(&mut self, name: &str) -> Result<()> {
+        let function = self.current_function()?;
+        let dead_block = self.context.append_basic_block(function, name);
+        self.switch_to_block(dead_block);
+
+        Ok(())
+    }
+
+    /// Finds the blocks of `function` that control can flow to from its entry block.
+    fn reachable_blocks(function: FunctionValue<'a>) -> HashSet<BasicBlock<'a>> {
+        let mut reachable = HashSet::new();
+        let mut pending: Vec<_> = function.get_first_basic_block().into_iter().collect();
+
+        while let Some(block) = pending.pop() {
+            if !reachable.insert(block) {
+                continue;
+            }
+
+            // The successors of a block are the blocks its terminator branches to.
+            if let Some(terminator) = block.get_terminator() {
+                pending.extend(
+                    terminator
+                        .get_operands()
+                        .flatten()
+                        .filter_map(|operand| operand.right()),
+                );
+            }
+        }
+
+        reachable
+    }
+
+    /// Runs the LLVM verifier over the module. Invalid IR is a bug in the compiler rather
+    /// than in the program, so it is reported as an internal error together with the IR of
+    /// the functions that failed to verify.
+    fn verify(&self) -> Result<()> {
+        if let Err(message) = self.module.verify() {
+            let functions = self
+                .module
+                .get_functions()
+                .filter(|function| !function.verify(false))
+                .map(|function| function.print_to_string().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            return Err(anyhow::anyhow!(
+                "internal compiler error: generated invalid LLVM IR\n{}\n{}",
+                message.to_string().trim_end(),
+                functions
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Attributes the instructions built from now on to `span` when emitting debug
+    /// information.
+    fn set_location(&self, span: &Span) {
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.set_location(self.context, &self.builder, span);
+        }
+    }
+
+    /// Resolves `name` to the innermost variable called that, falling back to the globals
+    /// and then the constants.
+    fn lookup(&self, name: &str) -> Option<Symbol<'a>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .or_else(|| self.globals.get(name).copied())
+            .map(Symbol::Variable)
+            .or_else(|| self.constants.get(name).copied().map(Symbol::Constant))
+    }
+
+    /// Finds the storage of the variable assigned to by `target`.
+    fn lookup_assignable(&self, target: &Identifier) -> Result<PointerValue<'a>> {
+        match self.lookup(&target.name) {
+            Some(Symbol::Variable(slot)) => Ok(slot),
+            Some(Symbol::Constant(_)) => Err(Diagnostic::new(
+                format!("cannot assign to constant `{}`", target.name),
+                target.span.clone(),
+            )
+            .into()),
+            None => Err(Diagnostic::new(
+                format!("cannot assign to undeclared variable `{}`", target.name),
+                target.span.clone(),
+            )
+            .into()),
+        }
+    }
+
+    /// Defines a global variable, whose initial value has to be known at compile time. Like
+    /// the functions other than `main`, it is internal to the module.
+    fn compile_global(&mut self, node: &Declaration) -> Result<()> {
+        let value = self.evaluate("global", &node.left, node.right.as_ref())?;
+
+        let i32_type = self.context.i32_type();
+        let global = self.module.add_global(i32_type, None, &node.left.name);
+        global.set_linkage(Linkage::Internal);
+        global.set_initializer(&i32_type.const_int(value as u64, true));
+        self.globals
+            .insert(node.left.name.clone(), global.as_pointer_value());
+
+        Ok(())
+    }
+
+    /// Evaluates the initializer `value` of the constant or global `name`. Errors keep the
+    /// span of the offending part of `value` and name the item being defined.
+    fn evaluate(&self, kind: &str, name: &Identifier, value: &dyn AstNode) -> Result<i32> {
+        ConstantEvaluator::evaluate(value, &self.constants).map_err(|error| {
+            match error.downcast::<Diagnostic>() {
+                Ok(diagnostic) => Diagnostic::new(
+                    format!(
+                        "cannot evaluate {} `{}`: {}",
+                        kind, name.name, diagnostic.message
+                    ),
+                    diagnostic.span,
+                )
+                .into(),
+                Err(error) => error,
+            }
+        })
+    }
+
+    /// Allocates a stack slot at the start of the current function's entry block, where
+    /// allocas have to live to be promoted to registers later on.
+    fn build_entry_alloca(&self, name: &str) -> Result<PointerValue<'a>> {
+        let function = self.current_function()?;
+        let entry = function
+            .get_first_basic_block()
+            .ok_or_else(|| anyhow::anyhow!("Function has no entry block"))?;
+
+        let builder = self.context.create_builder();
+        match entry.get_first_instruction() {
+            Some(instruction) => builder.position_before(&instruction),
+            None => builder.position_at_end(entry),
+        }
+        Ok(builder.build_alloca(self.context.i32_type(), name)?)
+    }
+
+    /// Branches from the current block to `target` unless the block already ends in a
+    /// terminator.
+    fn branch_to(&mut self, target: BasicBlock<'a>) -> Result<()> {
+        let current = self.current_block()?;
+        if current.get_terminator().is_none() {
+            self.builder.position_at_end(current);
+            self.builder.build_unconditional_branch(target)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lowers `&&` and `||`: the right operand is only evaluated in its own block when the
+    /// left one does not already decide the result, which is `short_circuit_value` if it does.
+    fn compile_short_circuit(
+        &mut self,
+        left: &dyn AstNode,
+        right: &dyn AstNode,
+        short_circuit_value: bool,
+        name: &str,
+    ) -> Result<()> {
+        let bool_type = self.context.bool_type();
+        let zero = self.context.i32_type().const_zero();
+
+        left.accept(self)?;
+        let left = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Left operand not found for logical {}", name))?;
+
+        let left_block = self.current_block()?;
+        let function = self.current_function()?;
+        let right_block = self
+            .context
+            .append_basic_block(function, &format!("{}_rhs", name));
+        let merge_block = self
+            .context
+            .append_basic_block(function, &format!("{}_merge", name));
+
+        self.builder.position_at_end(left_block);
+        let left = self.builder.build_int_compare(
+            IntPredicate::NE,
+            left,
+            zero,
+            &format!("{}_left", name),
+        )?;
+        if short_circuit_value {
+            self.builder
+                .build_conditional_branch(left, merge_block, right_block)?;
+        } else {
+            self.builder
+                .build_conditional_branch(left, right_block, merge_block)?;
         }
+
+        self.switch_to_block(right_block);
+        right.accept(self)?;
+        let right = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Right operand not found for logical {}", name))?;
+
+        // The right operand may have opened blocks of its own, so branch from wherever it ended.
+        let right_block = self.current_block()?;
+        self.builder.position_at_end(right_block);
+        let right = self.builder.build_int_compare(
+            IntPredicate::NE,
+            right,
+            zero,
+            &format!("{}_right", name),
+        )?;
+        self.builder.build_unconditional_branch(merge_block)?;
+
+        self.switch_to_block(merge_block);
+        let phi = self.builder.build_phi(bool_type, name)?;
+        let short_circuit = bool_type.const_int(short_circuit_value as u64, false);
+        phi.add_incoming(&[(&short_circuit, left_block), (&right, right_block)]);
+        self.intermediate_values
+            .push(self.builder.build_int_z_extend(
+                phi.as_basic_value().into_int_value(),
+                self.context.i32_type(),
+                &format!("{}_ext", name),
+            )?);
+
+        Ok(())
     }
 }
 
 impl NodeCompiler for LlvmCompiler<'_> {
     fn compile(&mut self) -> Result<String> {
-        self.builder.position_at_end(self.blocks.pop().unwrap());
-        let _ = self
-            .builder
-            .build_return(Some(&self.intermediate_values.pop().unwrap()));
-
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.finalize();
+        }
+        self.verify()?;
         Ok(self.module.print_to_string().to_string())
     }
 
+    fn compile_program(&mut self, node: &Program) -> Result<()> {
+        let mut names = HashSet::new();
+        for item in &node.items {
+            let name = item.name();
+            if !names.insert(name.name.as_str()) {
+                return Err(Diagnostic::new(
+                    format!("`{}` is defined more than once", name.name),
+                    name.span.clone(),
+                )
+                .into());
+            }
+        }
+
+        match node.items.iter().find_map(|item| match item {
+            Item::Function(function) if function.name.name == "main" => Some(function),
+            _ => None,
+        }) {
+            None => {
+                return Err(
+                    Diagnostic::new("program has no `main` function", node.span.clone()).into(),
+                );
+            }
+            Some(main) if !main.parameters.is_empty() => {
+                return Err(Diagnostic::new(
+                    "`main` must not take any parameters",
+                    main.name.span.clone(),
+                )
+                .into());
+            }
+            Some(_) => {}
+        }
+
+        // Every function is declared before any body is compiled, so calls can refer to
+        // functions defined further down the file, or to each other. Only `main` is
+        // visible to the linker, so a function named like a libc symbol cannot replace it.
+        let i32_type = self.context.i32_type();
+        for item in &node.items {
+            if let Item::Function(function) = item {
+                let parameter_types: Vec<BasicMetadataTypeEnum> =
+                    vec![i32_type.into(); function.parameters.len()];
+                let linkage = (function.name.name != "main").then_some(Linkage::Internal);
+                self.module.add_function(
+                    &function.name.name,
+                    i32_type.fn_type(&parameter_types, false),
+                    linkage,
+                );
+            }
+        }
+
+        // Constants may only use the ones defined before them, but function bodies see
+        // every constant and global.
+        for item in &node.items {
+            match item {
+                Item::Constant(constant) => self.compile_constant(constant)?,
+                Item::Global(global) => self.compile_global(global)?,
+                Item::Function(_) => {}
+            }
+        }
+
+        for item in &node.items {
+            if let Item::Function(function) = item {
+                self.compile_function(function)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates a constant, which only exists at compile time and so generates no code.
+    fn compile_constant(&mut self, node: &Constant) -> Result<()> {
+        let value = self.evaluate("constant", &node.name, node.value.as_ref())?;
+        self.constants.insert(node.name.name.clone(), value);
+
+        Ok(())
+    }
+
+    fn compile_negation(&mut self, node: &Negation) -> Result<()> {
+        node.value.accept(self)?;
+
+        let value = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Operand not found for negation"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        self.intermediate_values
+            .push(self.builder.build_int_neg(value, "neg")?);
+
+        Ok(())
+    }
+
     fn compile_sum(&mut self, node: &Addition) -> Result<()> {
         node.left.accept(self)?;
         node.right.accept(self)?;
@@ -59,9 +531,9 @@ impl NodeCompiler for LlvmCompiler<'_> {
             .pop()
             .ok_or_else(|| anyhow::anyhow!("Right operand not found for addition"))?;
 
-        self.builder.position_at_end(*self.blocks.last().unwrap());
+        self.builder.position_at_end(self.current_block()?);
         self.intermediate_values
-            .push(self.builder.build_int_add(left, right, "add").unwrap());
+            .push(self.builder.build_int_add(left, right, "add")?);
 
         Ok(())
     }
@@ -79,9 +551,9 @@ impl NodeCompiler for LlvmCompiler<'_> {
             .pop()
             .ok_or_else(|| anyhow::anyhow!("Right operand not found for subtraction"))?;
 
-        self.builder.position_at_end(*self.blocks.last().unwrap());
+        self.builder.position_at_end(self.current_block()?);
         self.intermediate_values
-            .push(self.builder.build_int_sub(left, right, "sub").unwrap());
+            .push(self.builder.build_int_sub(left, right, "sub")?);
 
         Ok(())
     }
@@ -99,9 +571,9 @@ impl NodeCompiler for LlvmCompiler<'_> {
             .pop()
             .ok_or_else(|| anyhow::anyhow!("Right operand not found for multiplication"))?;
 
-        self.builder.position_at_end(*self.blocks.last().unwrap());
+        self.builder.position_at_end(self.current_block()?);
         self.intermediate_values
-            .push(self.builder.build_int_mul(left, right, "mul").unwrap());
+            .push(self.builder.build_int_mul(left, right, "mul")?);
 
         Ok(())
     }
@@ -119,12 +591,29 @@ impl NodeCompiler for LlvmCompiler<'_> {
             .pop()
             .ok_or_else(|| anyhow::anyhow!("Right operand not found for division"))?;
 
-        self.builder.position_at_end(*self.blocks.last().unwrap());
-        self.intermediate_values.push(
-            self.builder
-                .build_int_signed_div(left, right, "div")
-                .unwrap(),
-        );
+        self.builder.position_at_end(self.current_block()?);
+        self.intermediate_values
+            .push(self.builder.build_int_signed_div(left, right, "div")?);
+
+        Ok(())
+    }
+
+    fn compile_remainder(&mut self, node: &Remainder) -> Result<()> {
+        node.left.accept(self)?;
+        node.right.accept(self)?;
+
+        let right = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Right operand not found for remainder"))?;
+        let left = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Left operand not found for remainder"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        self.intermediate_values
+            .push(self.builder.build_int_signed_rem(left, right, "rem")?);
 
         Ok(())
     }
@@ -132,20 +621,82 @@ impl NodeCompiler for LlvmCompiler<'_> {
     fn compile_logical_not(&mut self, node: &LogicalNot) -> Result<()> {
         node.value.accept(self)?;
 
+        let value = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Operand not found for logical not"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let zero = self.context.i32_type().const_zero();
+        let value = self
+            .builder
+            .build_int_compare(IntPredicate::EQ, value, zero, "not")?;
+        self.intermediate_values
+            .push(
+                self.builder
+                    .build_int_z_extend(value, self.context.i32_type(), "not_ext")?,
+            );
+
         Ok(())
     }
 
     fn compile_logical_or(&mut self, node: &LogicalOr) -> Result<()> {
+        self.compile_short_circuit(node.left.as_ref(), node.right.as_ref(), true, "or")
+    }
+
+    fn compile_logical_and(&mut self, node: &LogicalAnd) -> Result<()> {
+        self.compile_short_circuit(node.left.as_ref(), node.right.as_ref(), false, "and")
+    }
+
+    fn compile_equal(&mut self, node: &Equal) -> Result<()> {
         node.left.accept(self)?;
         node.right.accept(self)?;
 
+        let right = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Right operand not found for equality"))?;
+        let left = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Left operand not found for equality"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let value = self
+            .builder
+            .build_int_compare(IntPredicate::EQ, left, right, "eq")?;
+        self.intermediate_values
+            .push(
+                self.builder
+                    .build_int_z_extend(value, self.context.i32_type(), "eq_ext")?,
+            );
+
         Ok(())
     }
 
-    fn compile_logical_and(&mut self, node: &LogicalAnd) -> Result<()> {
+    fn compile_not_equal(&mut self, node: &NotEqual) -> Result<()> {
         node.left.accept(self)?;
         node.right.accept(self)?;
 
+        let right = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Right operand not found for inequality"))?;
+        let left = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Left operand not found for inequality"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let value = self
+            .builder
+            .build_int_compare(IntPredicate::NE, left, right, "ne")?;
+        self.intermediate_values
+            .push(
+                self.builder
+                    .build_int_z_extend(value, self.context.i32_type(), "ne_ext")?,
+            );
+
         Ok(())
     }
 
@@ -153,6 +704,25 @@ impl NodeCompiler for LlvmCompiler<'_> {
         node.left.accept(self)?;
         node.right.accept(self)?;
 
+        let right = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Right operand not found for less-than comparison"))?;
+        let left = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Left operand not found for less-than comparison"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let value = self
+            .builder
+            .build_int_compare(IntPredicate::SLT, left, right, "lt")?;
+        self.intermediate_values
+            .push(
+                self.builder
+                    .build_int_z_extend(value, self.context.i32_type(), "lt_ext")?,
+            );
+
         Ok(())
     }
 
@@ -160,6 +730,24 @@ impl NodeCompiler for LlvmCompiler<'_> {
         node.left.accept(self)?;
         node.right.accept(self)?;
 
+        let right = self.intermediate_values.pop().ok_or_else(|| {
+            anyhow::anyhow!("Right operand not found for greater-than comparison")
+        })?;
+        let left = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Left operand not found for greater-than comparison"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let value = self
+            .builder
+            .build_int_compare(IntPredicate::SGT, left, right, "gt")?;
+        self.intermediate_values
+            .push(
+                self.builder
+                    .build_int_z_extend(value, self.context.i32_type(), "gt_ext")?,
+            );
+
         Ok(())
     }
 
@@ -167,6 +755,23 @@ impl NodeCompiler for LlvmCompiler<'_> {
         node.left.accept(self)?;
         node.right.accept(self)?;
 
+        let right = self.intermediate_values.pop().ok_or_else(|| {
+            anyhow::anyhow!("Right operand not found for less-or-equal comparison")
+        })?;
+        let left = self.intermediate_values.pop().ok_or_else(|| {
+            anyhow::anyhow!("Left operand not found for less-or-equal comparison")
+        })?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let value = self
+            .builder
+            .build_int_compare(IntPredicate::SLE, left, right, "le")?;
+        self.intermediate_values
+            .push(
+                self.builder
+                    .build_int_z_extend(value, self.context.i32_type(), "le_ext")?,
+            );
+
         Ok(())
     }
 
@@ -174,32 +779,1091 @@ impl NodeCompiler for LlvmCompiler<'_> {
         node.left.accept(self)?;
         node.right.accept(self)?;
 
+        let right = self.intermediate_values.pop().ok_or_else(|| {
+            anyhow::anyhow!("Right operand not found for greater-or-equal comparison")
+        })?;
+        let left = self.intermediate_values.pop().ok_or_else(|| {
+            anyhow::anyhow!("Left operand not found for greater-or-equal comparison")
+        })?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let value = self
+            .builder
+            .build_int_compare(IntPredicate::SGE, left, right, "ge")?;
+        self.intermediate_values
+            .push(
+                self.builder
+                    .build_int_z_extend(value, self.context.i32_type(), "ge_ext")?,
+            );
+
+        Ok(())
+    }
+
+    fn compile_block(&mut self, node: &Block) -> Result<()> {
+        self.scopes.push(HashMap::new());
+        for node in &node.nodes {
+            self.set_location(node.span());
+            node.accept(self)?;
+        }
+        self.scopes.pop();
+
+        Ok(())
+    }
+
+    fn compile_if(&mut self, node: &If) -> Result<()> {
+        node.expression.accept(self)?;
+        let condition = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Condition not found for if"))?;
+
+        let current = self.current_block()?;
+        let function = self.current_function()?;
+        let then_block = self.context.append_basic_block(function, "then");
+        let else_block = node
+            .otherwise
+            .as_ref()
+            .map(|_| self.context.append_basic_block(function, "else"));
+        let merge_block = self.context.append_basic_block(function, "merge");
+
+        self.builder.position_at_end(current);
+        let zero = self.context.i32_type().const_zero();
+        let condition =
+            self.builder
+                .build_int_compare(IntPredicate::NE, condition, zero, "if_cond")?;
+        self.builder.build_conditional_branch(
+            condition,
+            then_block,
+            else_block.unwrap_or(merge_block),
+        )?;
+
+        // Values produced inside a branch do not dominate the merge block, so they are dropped.
+        let depth = self.intermediate_values.len();
+
+        self.switch_to_block(then_block);
+        node.then.accept(self)?;
+        self.intermediate_values.truncate(depth);
+        self.branch_to(merge_block)?;
+
+        if let (Some(otherwise), Some(else_block)) = (&node.otherwise, else_block) {
+            self.switch_to_block(else_block);
+            otherwise.accept(self)?;
+            self.intermediate_values.truncate(depth);
+            self.branch_to(merge_block)?;
+        }
+
+        self.switch_to_block(merge_block);
+
+        Ok(())
+    }
+
+    fn compile_while(&mut self, node: &While) -> Result<()> {
+        let function = self.current_function()?;
+        let header_block = self.context.append_basic_block(function, "while_header");
+        let body_block = self.context.append_basic_block(function, "while_body");
+        let exit_block = self.context.append_basic_block(function, "while_exit");
+
+        self.branch_to(header_block)?;
+
+        self.switch_to_block(header_block);
+        node.expression.accept(self)?;
+        let condition = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Condition not found for while"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let zero = self.context.i32_type().const_zero();
+        let condition =
+            self.builder
+                .build_int_compare(IntPredicate::NE, condition, zero, "while_cond")?;
+        self.builder
+            .build_conditional_branch(condition, body_block, exit_block)?;
+
+        let depth = self.intermediate_values.len();
+
+        self.switch_to_block(body_block);
+        self.loops.push(LoopTargets {
+            break_block: exit_block,
+            continue_block: header_block,
+        });
+        node.block.accept(self)?;
+        self.loops.pop();
+        self.intermediate_values.truncate(depth);
+        self.branch_to(header_block)?;
+
+        self.switch_to_block(exit_block);
+
+        Ok(())
+    }
+
+    fn compile_for(&mut self, node: &For) -> Result<()> {
+        node.start.accept(self)?;
+        node.end.accept(self)?;
+        let end = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("End not found for range"))?;
+        let start = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Start not found for range"))?;
+
+        let preheader_block = self.current_block()?;
+        let function = self.current_function()?;
+        let header_block = self.context.append_basic_block(function, "for_header");
+        let body_block = self.context.append_basic_block(function, "for_body");
+        let latch_block = self.context.append_basic_block(function, "for_latch");
+        let exit_block = self.context.append_basic_block(function, "for_exit");
+
+        self.branch_to(header_block)?;
+
+        // The index is kept in a register of its own, so assigning to the loop variable
+        // inside the body does not change how many times it runs.
+        self.switch_to_block(header_block);
+        let i32_type = self.context.i32_type();
+        let index = self.builder.build_phi(i32_type, "for_index")?;
+        let current = index.as_basic_value().into_int_value();
+        let predicate = if node.inclusive {
+            IntPredicate::SLE
+        } else {
+            IntPredicate::SLT
+        };
+        let condition = self
+            .builder
+            .build_int_compare(predicate, current, end, "for_cond")?;
+        self.builder
+            .build_conditional_branch(condition, body_block, exit_block)?;
+
+        let depth = self.intermediate_values.len();
+
+        self.switch_to_block(body_block);
+        let slot = self.build_entry_alloca(&node.variable.name)?;
+        self.builder.position_at_end(body_block);
+        self.builder.build_store(slot, current)?;
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.declare_variable(
+                self.context,
+                &node.variable.name,
+                slot,
+                &node.variable.span,
+                body_block,
+            );
+        }
+
+        self.scopes
+            .push(HashMap::from([(node.variable.name.clone(), slot)]));
+        self.loops.push(LoopTargets {
+            break_block: exit_block,
+            continue_block: latch_block,
+        });
+        node.block.accept(self)?;
+        self.loops.pop();
+        self.scopes.pop();
+        self.intermediate_values.truncate(depth);
+        self.branch_to(latch_block)?;
+
+        // `..=` stops after the iteration for `end` instead of stepping past it, which
+        // would overflow when `end` is `i32::MAX`.
+        self.switch_to_block(latch_block);
+        let next = self
+            .builder
+            .build_int_add(current, i32_type.const_int(1, false), "for_next")?;
+        if node.inclusive {
+            let last =
+                self.builder
+                    .build_int_compare(IntPredicate::EQ, current, end, "for_last")?;
+            self.builder
+                .build_conditional_branch(last, exit_block, header_block)?;
+        } else {
+            self.builder.build_unconditional_branch(header_block)?;
+        }
+        index.add_incoming(&[(&start, preheader_block), (&next, latch_block)]);
+
+        self.switch_to_block(exit_block);
+
+        Ok(())
+    }
+
+    fn compile_break(&mut self, node: &Break) -> Result<()> {
+        let target = self
+            .loops
+            .last()
+            .ok_or_else(|| Diagnostic::new("`break` outside of a loop", node.span.clone()))?
+            .break_block;
+        self.branch_to(target)?;
+
+        self.start_dead_block("after_break")
+    }
+
+    fn compile_continue(&mut self, node: &Continue) -> Result<()> {
+        let target = self
+            .loops
+            .last()
+            .ok_or_else(|| Diagnostic::new("`continue` outside of a loop", node.span.clone()))?
+            .continue_block;
+        self.branch_to(target)?;
+
+        self.start_dead_block("after_continue")
+    }
+
+    fn compile_function(&mut self, node: &Function) -> Result<()> {
+        let name = &node.name.name;
+        let function = self
+            .module
+            .get_function(name)
+            .ok_or_else(|| anyhow::anyhow!("Function `{}` was not declared", name))?;
+        let entry = self.context.append_basic_block(function, "entry");
+
+        // Functions share nothing but the globals and constants: each one gets its own
+        // blocks, variables and values.
+        self.blocks.push(entry);
+        self.builder.position_at_end(entry);
+        self.scopes = vec![HashMap::new()];
+
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.add_function(function, name, node.span.start.line as u32);
+        }
+        self.set_location(&node.span);
+
+        for (parameter, value) in node.parameters.iter().zip(function.get_param_iter()) {
+            if self.scopes[0].contains_key(&parameter.name) {
+                return Err(Diagnostic::new(
+                    format!("parameter `{}` is declared more than once", parameter.name),
+                    parameter.span.clone(),
+                )
+                .into());
+            }
+
+            value.set_name(&parameter.name);
+            let slot = self.build_entry_alloca(&parameter.name)?;
+            self.builder.position_at_end(entry);
+            self.builder.build_store(slot, value.into_int_value())?;
+
+            if let Some(debug_info) = &self.debug_info {
+                debug_info.declare_variable(
+                    self.context,
+                    &parameter.name,
+                    slot,
+                    &parameter.span,
+                    entry,
+                );
+            }
+            self.scopes[0].insert(parameter.name.clone(), slot);
+        }
+
+        node.body.accept(self)?;
+
+        // The body only falls off its end if the block it finished in is still open and
+        // reachable. Code following a `return`, or the merge block of an `if` whose
+        // branches both return, is dead and so does not need a `return` of its own.
+        let last = self.current_block()?;
+        if last.get_terminator().is_none() {
+            if Self::reachable_blocks(function).contains(&last) {
+                return Err(Diagnostic::new(
+                    format!(
+                        "function `{}` can reach the end of its body without returning a value",
+                        name
+                    ),
+                    node.body.span().last_character(),
+                )
+                .into());
+            }
+
+            self.builder.position_at_end(last);
+            self.builder.build_unreachable()?;
+        }
+
+        self.intermediate_values.clear();
+        self.scopes.clear();
+        self.blocks.pop();
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.leave_function();
+            self.builder.unset_current_debug_location();
+        }
+
+        Ok(())
+    }
+
+    fn compile_call(&mut self, node: &Call) -> Result<()> {
+        let name = &node.name.name;
+        let function = self.module.get_function(name).ok_or_else(|| {
+            Diagnostic::new(
+                format!("call to undefined function `{}`", name),
+                node.name.span.clone(),
+            )
+        })?;
+
+        let expected = function.count_params() as usize;
+        if node.arguments.len() != expected {
+            return Err(Diagnostic::new(
+                format!(
+                    "function `{}` takes {} argument(s) but {} were supplied",
+                    name,
+                    expected,
+                    node.arguments.len()
+                ),
+                node.span.clone(),
+            )
+            .into());
+        }
+
+        let mut arguments: Vec<BasicMetadataValueEnum> = Vec::new();
+        for argument in &node.arguments {
+            argument.accept(self)?;
+            let value = self
+                .intermediate_values
+                .pop()
+                .ok_or_else(|| anyhow::anyhow!("Argument not found for call"))?;
+            arguments.push(value.into());
+        }
+
+        self.builder.position_at_end(self.current_block()?);
+        let value = self
+            .builder
+            .build_call(function, &arguments, "call")?
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| anyhow::anyhow!("Function `{}` does not return a value", name))?;
+        self.intermediate_values.push(value.into_int_value());
+
+        Ok(())
+    }
+
+    fn compile_return(&mut self, node: &Return) -> Result<()> {
+        node.value.accept(self)?;
+
+        let value = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Value not found for return"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        self.builder.build_return(Some(&value))?;
+
+        self.start_dead_block("after_return")
+    }
+
+    fn compile_declaration(&mut self, node: &Declaration) -> Result<()> {
+        // The initializer is compiled first so that it still sees any variable being shadowed.
+        node.right.accept(self)?;
+
+        let right = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Value not found for declaration"))?;
+
+        let slot = self.build_entry_alloca(&node.left.name)?;
+        self.builder.position_at_end(self.current_block()?);
+        self.builder.build_store(slot, right)?;
+
+        if let Some(debug_info) = &self.debug_info {
+            let block = self.current_block()?;
+            debug_info.declare_variable(self.context, &node.left.name, slot, &node.span, block);
+        }
+
+        self.scopes
+            .last_mut()
+            .ok_or_else(|| anyhow::anyhow!("No scope to declare `{}` in", node.left.name))?
+            .insert(node.left.name.clone(), slot);
+
         Ok(())
     }
 
     fn compile_assignment(&mut self, node: &Assignment) -> Result<()> {
-        node.left.accept(self)?;
+        let slot = self.lookup_assignable(&node.left)?;
+
         node.right.accept(self)?;
 
         let right = self
             .intermediate_values
             .pop()
-            .ok_or_else(|| anyhow::anyhow!("Left operand not found for division"))?;
+            .ok_or_else(|| anyhow::anyhow!("Value not found for assignment"))?;
 
-        self.builder.position_at_end(*self.blocks.last().unwrap());
+        self.builder.position_at_end(self.current_block()?);
+        self.builder.build_store(slot, right)?;
+
+        Ok(())
+    }
+
+    fn compile_compound_assignment(&mut self, node: &CompoundAssignment) -> Result<()> {
+        let slot = self.lookup_assignable(&node.left)?;
+
+        node.right.accept(self)?;
+
+        let right = self
+            .intermediate_values
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Value not found for compound assignment"))?;
+
+        self.builder.position_at_end(self.current_block()?);
+        let left = self
+            .builder
+            .build_load(self.context.i32_type(), slot, &node.left.name)?
+            .into_int_value();
+        let value = match node.operator {
+            CompoundOperator::Add => self.builder.build_int_add(left, right, "add"),
+            CompoundOperator::Subtract => self.builder.build_int_sub(left, right, "sub"),
+            CompoundOperator::Multiply => self.builder.build_int_mul(left, right, "mul"),
+            CompoundOperator::Divide => self.builder.build_int_signed_div(left, right, "div"),
+            CompoundOperator::Remainder => self.builder.build_int_signed_rem(left, right, "rem"),
+        }?;
+        self.builder.build_store(slot, value)?;
 
         Ok(())
     }
 
     fn compile_identifier(&mut self, node: &Identifier) -> Result<()> {
+        let value = match self.lookup(&node.name) {
+            Some(Symbol::Variable(slot)) => {
+                self.builder.position_at_end(self.current_block()?);
+                self.builder
+                    .build_load(self.context.i32_type(), slot, &node.name)?
+                    .into_int_value()
+            }
+            Some(Symbol::Constant(value)) => self.context.i32_type().const_int(value as u64, true),
+            None => {
+                return Err(Diagnostic::new(
+                    format!("use of undeclared variable `{}`", node.name),
+                    node.span.clone(),
+                )
+                .into());
+            }
+        };
+        self.intermediate_values.push(value);
+
         Ok(())
     }
 
     fn compile_int_lit(&mut self, node: &Integer) -> Result<()> {
         let i32_type = self.context.i32_type();
-        let value = i32_type.const_int(node.value.try_into().unwrap(), false);
+        let value = i32_type.const_int(node.value as u64, true);
         self.intermediate_values.push(value);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        compiler::{NodeCompiler, llvmcompiler::LlvmCompiler},
+        diagnostics::Diagnostic,
+        lexer::Lexer,
+        parser,
+    };
+    use anyhow::Result;
+    use inkwell::{OptimizationLevel, context::Context};
+    use std::path::Path;
+
+    /// Parses `source`, which must be free of syntax errors, and compiles its items
+    /// with `compiler`.
+    fn accept(compiler: &mut LlvmCompiler, source: &str) -> Result<()> {
+        let tokens = Lexer::new("test", source).tokenize();
+        let program = parser::parse(tokens.stream());
+        assert!(tokens.into_diagnostics().is_empty());
+
+        program.accept(compiler)
+    }
+
+    /// Compiles the items of `source` into a module of `context`, stopping short of
+    /// `compile` so the module can still be inspected or changed.
+    fn build<'a>(context: &'a Context, source: &str) -> LlvmCompiler<'a> {
+        let mut compiler = LlvmCompiler::new("test", context);
+        accept(&mut compiler, source).unwrap();
+        compiler
+    }
+
+    fn compile(source: &str) -> String {
+        let context = Context::create();
+        build(&context, source).compile().unwrap()
+    }
+
+    fn run(source: &str) -> i32 {
+        let context = Context::create();
+        let mut compiler = build(&context, source);
+        compiler.compile().unwrap();
+
+        compiler.run(OptimizationLevel::None).unwrap()
+    }
+
+    /// Compiles a program whose `main` returns `expression`.
+    fn compile_expression(expression: &str) -> String {
+        compile(&format!("fn main() -> i32 {{ return {}; }}", expression))
+    }
+
+    /// Compiles `source`, which must fail with a diagnostic pointing into it.
+    fn compile_error(source: &str) -> String {
+        let context = Context::create();
+        let mut compiler = LlvmCompiler::new("test", &context);
+        match accept(&mut compiler, source) {
+            Ok(()) => panic!("expected `{}` to fail to compile", source),
+            Err(error) => error
+                .downcast::<Diagnostic>()
+                .expect("expected a diagnostic")
+                .to_string(),
+        }
+    }
+
+    fn returning(value: &str) -> String {
+        format!(
+            "; ModuleID = 'test'\n\
+             source_filename = \"test\"\n\
+             \n\
+             define i32 @main() {{\n\
+             entry:\n  \
+             ret i32 {}\n\
+             }}\n",
+            value
+        )
+    }
+
+    /// Compiles `a <operator> b` on two parameters, which unlike literals are not
+    /// folded into a constant by the builder.
+    fn compile_comparison(operator: &str) -> String {
+        compile(&format!(
+            "fn f(a: i32, b: i32) -> i32 {{ return a {} b; }} \
+             fn main() -> i32 {{ return f(1, 2); }}",
+            operator
+        ))
+    }
+
+    /// The lowering of a comparison named `name`: an `icmp` of the loaded parameters,
+    /// widened back to `i32`. The parameters are `%a` and `%b` and their slots `%a1`
+    /// and `%b2`, so the loads are `%a3` and `%b4`.
+    fn comparison(predicate: &str, name: &str) -> String {
+        format!(
+            "  %{name} = icmp {predicate} i32 %a3, %b4\n  \
+             %{name}_ext = zext i1 %{name} to i32\n  \
+             ret i32 %{name}_ext\n"
+        )
+    }
+
+    #[test]
+    fn test_less() {
+        assert_eq!(compile_expression("-1 < 1"), returning("1"));
+        assert_eq!(compile_expression("2 < 2"), returning("0"));
+        assert!(compile_comparison("<").contains(&comparison("slt", "lt")));
+    }
+
+    #[test]
+    fn test_greater() {
+        assert_eq!(compile_expression("1 > -1"), returning("1"));
+        assert_eq!(compile_expression("2 > 2"), returning("0"));
+        assert!(compile_comparison(">").contains(&comparison("sgt", "gt")));
+    }
+
+    #[test]
+    fn test_less_equal() {
+        assert_eq!(compile_expression("2 <= 2"), returning("1"));
+        assert_eq!(compile_expression("3 <= -3"), returning("0"));
+        assert!(compile_comparison("<=").contains(&comparison("sle", "le")));
+    }
+
+    #[test]
+    fn test_greater_equal() {
+        assert_eq!(compile_expression("-2 >= -2"), returning("1"));
+        assert_eq!(compile_expression("-3 >= 3"), returning("0"));
+        assert!(compile_comparison(">=").contains(&comparison("sge", "ge")));
+    }
+
+    #[test]
+    fn test_logical_not() {
+        assert_eq!(compile_expression("!0"), returning("1"));
+        assert_eq!(compile_expression("!-5"), returning("0"));
+        assert_eq!(compile_expression("!!7"), returning("1"));
+    }
+
+    #[test]
+    fn test_logical_and() {
+        let ir = compile_expression("1 && 0");
+        assert!(ir.contains("br i1 true, label %and_rhs, label %and_merge"));
+        assert!(ir.contains("%and = phi i1 [ false, %entry ], [ false, %and_rhs ]"));
+        assert!(ir.contains("ret i32 %and_ext"));
+
+        // `zero` already makes the first `&&` false, so only the second `bump` runs.
+        let source = "
+            let counter: i32 = 0;
+
+            fn bump() -> i32 {
+                counter += 1;
+                return 1;
+            }
+
+            fn main() -> i32 {
+                let zero: i32 = 0;
+                let one: i32 = 1;
+                zero && bump();
+                one && bump();
+                return counter;
+            }
+        ";
+        assert_eq!(run(source), 1);
+    }
+
+    #[test]
+    fn test_logical_or() {
+        let ir = compile_expression("0 || 2");
+        assert!(ir.contains("br i1 false, label %or_merge, label %or_rhs"));
+        assert!(ir.contains("%or = phi i1 [ true, %entry ], [ true, %or_rhs ]"));
+        assert!(ir.contains("ret i32 %or_ext"));
+
+        // `one` already makes the first `||` true, so only the second `bump` runs.
+        let source = "
+            let counter: i32 = 0;
+
+            fn bump() -> i32 {
+                counter += 1;
+                return 1;
+            }
+
+            fn main() -> i32 {
+                let zero: i32 = 0;
+                let one: i32 = 1;
+                one || bump();
+                zero || bump();
+                return counter;
+            }
+        ";
+        assert_eq!(run(source), 1);
+    }
+
+    #[test]
+    fn test_if() {
+        let ir = compile("fn main() -> i32 { if 1 < 2 { 3 } else { 4 } return 5; }");
+        assert!(ir.contains("br i1 true, label %then, label %else"));
+        assert!(ir.contains("then:"));
+        assert!(ir.contains("else:"));
+        assert!(ir.contains("merge:"));
+        assert!(ir.ends_with("  ret i32 5\n}\n"));
+
+        let ir = compile("fn main() -> i32 { if 0 { 3 } return 5; }");
+        assert!(ir.contains("br i1 false, label %then, label %merge"));
+        assert!(!ir.contains("else:"));
+    }
+
+    #[test]
+    fn test_while() {
+        let ir = compile("fn main() -> i32 { while 0 { 1 } return 2; }");
+        assert!(ir.contains("br label %while_header"));
+        assert!(ir.contains("br i1 false, label %while_body, label %while_exit"));
+        assert!(ir.ends_with("  ret i32 2\n}\n"));
+    }
+
+    #[test]
+    fn test_variables() {
+        let ir = compile("fn main() -> i32 { let x: i32 = 5; x = x + 2; x *= 3; return x; }");
+        assert!(
+            ir.contains("entry:\n  %x = alloca i32, align 4\n  store i32 5, ptr %x, align 4\n")
+        );
+        assert!(ir.contains("%x1 = load i32, ptr %x, align 4\n  %add = add i32 %x1, 2\n  store i32 %add, ptr %x, align 4\n"));
+        assert!(ir.contains("%x2 = load i32, ptr %x, align 4\n  %mul = mul i32 %x2, 3\n  store i32 %mul, ptr %x, align 4\n"));
+        assert!(ir.ends_with("  %x3 = load i32, ptr %x, align 4\n  ret i32 %x3\n}\n"));
+    }
+
+    #[test]
+    fn test_scopes() {
+        // The loop body's declaration is hoisted next to `x` in the entry block.
+        let ir = compile(
+            "fn main() -> i32 { let x: i32 = 1; while x < 3 { let y: i32 = x; x += y; } return x; }",
+        );
+        assert!(ir.contains("entry:\n  %y = alloca i32, align 4\n  %x = alloca i32, align 4\n"));
+
+        let error = compile_error("fn main() -> i32 { { let y: i32 = 1; } return y; }");
+        assert_eq!(error, "test:1:47: use of undeclared variable `y`");
+
+        let error = compile_error("fn main() -> i32 { let x: i32 = 1; z -= x; return x; }");
+        assert_eq!(error, "test:1:36: cannot assign to undeclared variable `z`");
+    }
+
+    #[test]
+    fn test_main_program() {
+        let ir = compile(include_str!("../../main.d"));
+        assert!(ir.contains("br i1 false, label %then, label %else"));
+        assert!(ir.contains("br i1 %while_cond, label %while_body, label %while_exit"));
+        assert!(ir.contains("ret i32 %x"));
+    }
+
+    #[test]
+    fn test_program_errors() {
+        assert_eq!(
+            compile_error("fn f() -> i32 { return 1; }"),
+            "test:1:1: program has no `main` function"
+        );
+        assert_eq!(
+            compile_error("fn main(a: i32) -> i32 { return a; }"),
+            "test:1:4: `main` must not take any parameters"
+        );
+        assert_eq!(
+            compile_error("fn main() -> i32 { return 0; } let main: i32;"),
+            "test:1:36: `main` is defined more than once"
+        );
+    }
+
+    #[test]
+    fn test_globals() {
+        let source = "
+            const LIMIT: i32 = 3 * 4;
+            let counter: i32 = LIMIT - 2;
+
+            fn main() -> i32 {
+                bump();
+                bump();
+                return counter + LIMIT;
+            }
+
+            fn bump() -> i32 {
+                counter += 1;
+                return counter;
+            }
+        ";
+        let ir = compile(source);
+        assert!(ir.contains("@counter = internal global i32 10"));
+        assert!(!ir.contains("LIMIT"));
+        assert_eq!(run(source), 12 + 12);
+
+        assert_eq!(
+            compile_error("const N: i32 = 1; fn main() -> i32 { N = 2; return N; }"),
+            "test:1:38: cannot assign to constant `N`"
+        );
+        assert_eq!(
+            compile_error("fn main() -> i32 { return 0; } let g: i32 = main();"),
+            "test:1:45: cannot evaluate global `g`: expected a constant expression"
+        );
+        assert_eq!(
+            compile_error("const A: i32 = B; const B: i32 = 1; fn main() -> i32 { return A; }"),
+            "test:1:16: cannot evaluate constant `A`: `B` is not a constant"
+        );
+        assert_eq!(
+            compile_error("const A: i32 = 1 / 0; fn main() -> i32 { return A; }"),
+            "test:1:16: cannot evaluate constant `A`: \
+             constant expression divides by zero or overflows"
+        );
+    }
+
+    #[test]
+    fn test_verifier_failure() {
+        let context = Context::create();
+        let mut compiler = build(&context, "fn main() -> i32 { return 0; }");
+
+        // A block left without a terminator is invalid IR that only the verifier catches.
+        let function = compiler.module.get_function("main").unwrap();
+        context.append_basic_block(function, "dangling");
+
+        let error = compiler.compile().unwrap_err().to_string();
+        assert!(error.starts_with("internal compiler error: generated invalid LLVM IR\n"));
+        assert!(error.contains("define i32 @main()"));
+        assert!(error.contains("dangling:"));
+    }
+
+    #[test]
+    fn test_optimize() {
+        let source = "fn main() -> i32 {
+            let x: i32 = 0;
+            let i: i32 = 0;
+            while i < 4 { x += i; i += 1; }
+            return x * 2;
+        }";
+
+        let context = Context::create();
+        let mut compiler = build(&context, source);
+
+        let ir = compiler.compile().unwrap();
+        assert!(ir.contains("alloca"));
+
+        let ir = compiler.optimize(OptimizationLevel::None).unwrap();
+        assert!(ir.contains("alloca"));
+
+        // mem2reg promotes the stack slots, after which the whole loop folds away.
+        let ir = compiler.optimize(OptimizationLevel::Less).unwrap();
+        assert!(!ir.contains("alloca"));
+        assert!(ir.contains("ret i32 12"));
+    }
+
+    #[test]
+    fn test_run() {
+        assert_eq!(run(include_str!("../../main.d")), 16);
+    }
+
+    #[test]
+    fn test_debug_info() {
+        let source =
+            "fn main() -> i32 {\n    let x: i32 = 1;\n    let y: i32 = x * 2;\n    return y;\n}";
+
+        let context = Context::create();
+        let mut compiler = LlvmCompiler::new("test", &context);
+        compiler
+            .enable_debug_info(Path::new("dir/test.d"), false)
+            .unwrap();
+        accept(&mut compiler, source).unwrap();
+
+        let ir = compiler.compile().unwrap();
+        assert!(ir.contains("!llvm.dbg.cu"));
+        assert!(ir.contains("!DIFile(filename: \"test.d\", directory: \"dir\")"));
+        assert!(ir.contains("!DISubprogram(name: \"main\""));
+        assert!(ir.contains("!DILocalVariable(name: \"x\""));
+        assert!(ir.contains("!DILocalVariable(name: \"y\""));
+        assert!(ir.contains("call void @llvm.dbg.declare(metadata ptr %y"));
+        assert!(ir.contains("!DILocation(line: 4, column: 5"));
+    }
+
+    #[test]
+    fn test_emit() {
+        let context = Context::create();
+        let mut compiler = build(
+            &context,
+            "fn main() -> i32 { let x: i32 = 2; return x * 21; }",
+        );
+        compiler.compile().unwrap();
+
+        let directory = std::env::temp_dir();
+        let object = directory.join(format!("test_emit_{}.o", std::process::id()));
+        let assembly = directory.join(format!("test_emit_{}.s", std::process::id()));
+        compiler
+            .emit(&object, &assembly, OptimizationLevel::None)
+            .unwrap();
+
+        assert!(std::fs::metadata(&object).unwrap().len() > 0);
+        assert!(std::fs::read_to_string(&assembly).unwrap().contains("main"));
+
+        std::fs::remove_file(object).unwrap();
+        std::fs::remove_file(assembly).unwrap();
+    }
+
+    #[test]
+    fn test_functions() {
+        let ir = compile(
+            "fn add(a: i32, b: i32) -> i32 { return a + b; } fn main() -> i32 { return add(1, 2); }",
+        );
+        assert!(ir.contains("define internal i32 @add(i32 %a, i32 %b) {"));
+        assert!(ir.contains("%call = call i32 @add(i32 1, i32 2)"));
+
+        let source = "
+            fn main() -> i32 {
+                return factorial(5) + fibonacci(10) + is_even(7);
+            }
+
+            fn factorial(n: i32) -> i32 {
+                if n < 2 {
+                    return 1;
+                }
+                return n * factorial(n - 1);
+            }
+
+            fn fibonacci(n: i32) -> i32 {
+                if n < 2 {
+                    return n;
+                } else {
+                    return fibonacci(n - 1) + fibonacci(n - 2);
+                }
+            }
+
+            fn is_even(n: i32) -> i32 {
+                if n == 0 {
+                    return 1;
+                }
+                return is_odd(n - 1);
+            }
+
+            fn is_odd(n: i32) -> i32 {
+                if n == 0 {
+                    return 0;
+                }
+                return is_even(n - 1);
+            }
+        ";
+        assert_eq!(run(source), 120 + 55);
+    }
+
+    #[test]
+    fn test_function_errors() {
+        assert_eq!(
+            compile_error("fn main() -> i32 { return f(1); }"),
+            "test:1:27: call to undefined function `f`"
+        );
+        assert_eq!(
+            compile_error("fn f(a: i32) -> i32 { return a; } fn main() -> i32 { return f(1, 2); }"),
+            "test:1:61: function `f` takes 1 argument(s) but 2 were supplied"
+        );
+        assert_eq!(
+            compile_error(
+                "fn f(a: i32, a: i32) -> i32 { return a; } fn main() -> i32 { return 0; }"
+            ),
+            "test:1:14: parameter `a` is declared more than once"
+        );
+        assert_eq!(
+            compile_error(
+                "fn f() -> i32 { return 1; } fn f() -> i32 { return 2; } fn main() -> i32 { return 0; }"
+            ),
+            "test:1:32: `f` is defined more than once"
+        );
+        assert_eq!(
+            compile_error(
+                "fn f(a: i32) -> i32 { if a { return 1; } } fn main() -> i32 { return 0; }"
+            ),
+            "test:1:42: function `f` can reach the end of its body without returning a value"
+        );
+        assert_eq!(
+            compile_error(
+                "fn f() -> i32 { let x: i32 = 1; return x; } fn main() -> i32 { return x; }"
+            ),
+            "test:1:71: use of undeclared variable `x`"
+        );
+    }
+
+    #[test]
+    fn test_return() {
+        // Code after a `return` is compiled into a block nothing branches to.
+        let source = "fn main() -> i32 { return 1; let x: i32 = 2; return x; }";
+        assert!(compile(source).contains("after_return:"));
+        assert_eq!(run(source), 1);
+
+        let source = "
+            fn root(n: i32) -> i32 {
+                let i: i32 = 0;
+                while i <= n {
+                    if i * i >= n {
+                        return i;
+                    }
+                    i += 1;
+                }
+                return -1;
+            }
+
+            fn sign(n: i32) -> i32 {
+                if n < 0 {
+                    return -1;
+                } else {
+                    if n == 0 {
+                        return 0;
+                    }
+                    return 1;
+                }
+                n = 2;
+            }
+
+            fn main() -> i32 {
+                return root(50) * sign(-7);
+            }
+        ";
+        assert_eq!(run(source), -8);
+    }
+
+    #[test]
+    fn test_missing_return() {
+        assert_eq!(
+            compile_error("fn main() -> i32 {\n    let x: i32 = 1;\n    if x { return x; }\n}"),
+            "test:4:1: function `main` can reach the end of its body without returning a value"
+        );
+        assert_eq!(
+            compile_error("fn main() -> i32 { while 1 { return 0; } }"),
+            "test:1:42: function `main` can reach the end of its body without returning a value"
+        );
+    }
+
+    #[test]
+    fn test_break_continue() {
+        let source = "
+            fn main() -> i32 {
+                let sum: i32 = 0;
+                let i: i32 = 0;
+                while 1 {
+                    i += 1;
+                    if i % 2 == 0 {
+                        continue;
+                    }
+                    if i > 9 {
+                        break;
+                    }
+                    sum += i;
+                }
+                return sum;
+            }
+        ";
+        let ir = compile(source);
+        assert!(ir.contains("br label %while_exit"));
+        assert!(ir.contains("after_break:"));
+        assert!(ir.contains("after_continue:"));
+        assert_eq!(run(source), 1 + 3 + 5 + 7 + 9);
+
+        // Both apply to the innermost loop only.
+        let source = "
+            fn main() -> i32 {
+                let count: i32 = 0;
+                let i: i32 = 0;
+                while i < 3 {
+                    i += 1;
+                    let j: i32 = 0;
+                    while 1 {
+                        j += 1;
+                        if j == 2 {
+                            continue;
+                        }
+                        if j > 4 {
+                            break;
+                        }
+                        count += 1;
+                    }
+                }
+                return count;
+            }
+        ";
+        assert_eq!(run(source), 3 * 3);
+    }
+
+    #[test]
+    fn test_for() {
+        let ir = compile(
+            "fn main() -> i32 { let sum: i32 = 0; for i in 0..4 { sum += i; } return sum; }",
+        );
+        assert!(ir.contains("%for_index = phi i32 [ 0, %entry ], [ %for_next, %for_latch ]"));
+        assert!(ir.contains("%for_cond = icmp slt i32 %for_index, 4"));
+        assert!(ir.contains("br label %for_header"));
+
+        let source = "
+            const N: i32 = 10;
+
+            fn main() -> i32 {
+                let sum: i32 = 0;
+                for i in 1..=N {
+                    if i % 3 == 0 {
+                        continue;
+                    }
+                    if i == 8 {
+                        break;
+                    }
+                    // Does not change how often the loop runs.
+                    i = 100;
+                    sum += i;
+                }
+                for i in 5..5 {
+                    return -1;
+                }
+                return sum;
+            }
+        ";
+        assert_eq!(run(source), 100 * 5);
+
+        // Stepping past `end` would overflow, so `..=` has to stop at it.
+        let source = "
+            fn main() -> i32 {
+                let count: i32 = 0;
+                for i in 2147483645..=2147483647 {
+                    count += 1;
+                }
+                return count;
+            }
+        ";
+        assert_eq!(run(source), 3);
+
+        assert_eq!(
+            compile_error("fn main() -> i32 { for i in 0..3 { } return i; }"),
+            "test:1:45: use of undeclared variable `i`"
+        );
+    }
+
+    #[test]
+    fn test_comparison_as_integer() {
+        assert_eq!(compile_expression("(1 < 2) + (3 >= 3) + 5"), returning("7"));
+    }
+}
@@ -1,30 +1,57 @@
 use crate::ast::{
-    arithmetic::{Addition, Division, Multiplication, Subtraction},
-    boolean::{Greater, GreaterEqual, Less, LessEqual, LogicalAnd, LogicalNot, LogicalOr},
-    lvalues::{Assignment, Identifier},
+    arithmetic::{Addition, Division, Multiplication, Negation, Remainder, Subtraction},
+    boolean::{
+        Equal, Greater, GreaterEqual, Less, LessEqual, LogicalAnd, LogicalNot, LogicalOr, NotEqual,
+    },
+    control_flow::{Block, Break, Continue, For, If, While},
+    functions::{Call, Function, Return},
+    lvalues::{Assignment, CompoundAssignment, Declaration, Identifier},
+    program::{Constant, Program},
     rvalues::Integer,
 };
 use anyhow::Result;
 
+pub mod constants;
+pub mod debuginfo;
 pub mod llvmcompiler;
 
 pub trait NodeCompiler {
     fn compile(&mut self) -> Result<String>;
 
+    fn compile_program(&mut self, node: &Program) -> Result<()>;
+    fn compile_constant(&mut self, node: &Constant) -> Result<()>;
+
+    fn compile_negation(&mut self, node: &Negation) -> Result<()>;
     fn compile_sum(&mut self, node: &Addition) -> Result<()>;
     fn compile_subtraction(&mut self, node: &Subtraction) -> Result<()>;
     fn compile_multiplication(&mut self, node: &Multiplication) -> Result<()>;
     fn compile_division(&mut self, node: &Division) -> Result<()>;
+    fn compile_remainder(&mut self, node: &Remainder) -> Result<()>;
 
     fn compile_logical_not(&mut self, node: &LogicalNot) -> Result<()>;
     fn compile_logical_or(&mut self, node: &LogicalOr) -> Result<()>;
     fn compile_logical_and(&mut self, node: &LogicalAnd) -> Result<()>;
+    fn compile_equal(&mut self, node: &Equal) -> Result<()>;
+    fn compile_not_equal(&mut self, node: &NotEqual) -> Result<()>;
     fn compile_less(&mut self, node: &Less) -> Result<()>;
     fn compile_greater(&mut self, node: &Greater) -> Result<()>;
     fn compile_less_equal(&mut self, node: &LessEqual) -> Result<()>;
     fn compile_greater_equal(&mut self, node: &GreaterEqual) -> Result<()>;
 
+    fn compile_block(&mut self, node: &Block) -> Result<()>;
+    fn compile_if(&mut self, node: &If) -> Result<()>;
+    fn compile_while(&mut self, node: &While) -> Result<()>;
+    fn compile_for(&mut self, node: &For) -> Result<()>;
+    fn compile_break(&mut self, node: &Break) -> Result<()>;
+    fn compile_continue(&mut self, node: &Continue) -> Result<()>;
+
+    fn compile_function(&mut self, node: &Function) -> Result<()>;
+    fn compile_call(&mut self, node: &Call) -> Result<()>;
+    fn compile_return(&mut self, node: &Return) -> Result<()>;
+
+    fn compile_declaration(&mut self, node: &Declaration) -> Result<()>;
     fn compile_assignment(&mut self, node: &Assignment) -> Result<()>;
+    fn compile_compound_assignment(&mut self, node: &CompoundAssignment) -> Result<()>;
     fn compile_identifier(&mut self, node: &Identifier) -> Result<()>;
 
     fn compile_int_lit(&mut self, node: &Integer) -> Result<()>;
@@ -0,0 +1,92 @@
+use crate::span::Span;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+pub type Result<T> = std::result::Result<T, Diagnostic>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Formats the diagnostic together with the offending source line and a
+    /// caret underline below the spanned characters.
+    pub fn render(&self, source: &str) -> String {
+        let start = self.span.start;
+        let end = self.span.end;
+        let line = source
+            .lines()
+            .nth(start.line.saturating_sub(1))
+            .unwrap_or_default();
+        let gutter = " ".repeat(start.line.to_string().len());
+
+        let line_length = line.chars().count() + 1;
+        let underline_end = if end.line == start.line {
+            end.column.min(line_length)
+        } else {
+            line_length
+        };
+        let underline = "^".repeat(underline_end.saturating_sub(start.column).max(1));
+
+        format!(
+            "error: {}\n{gutter}--> {}\n{gutter} |\n{} | {}\n{gutter} | {}{}",
+            self.message,
+            self.span,
+            start.line,
+            line,
+            " ".repeat(start.column.saturating_sub(1)),
+            underline,
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+#[cfg(test)]
+mod tests {
+    use crate::span::{Position, Span};
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let source = "let x: i32 = 5;\nlet y: i32 = x +;\n";
+        let span = Span::new(
+            "main.d".into(),
+            Position {
+                offset: 31,
+                line: 2,
+                column: 16,
+            },
+            Position {
+                offset: 32,
+                line: 2,
+                column: 17,
+            },
+        );
+        let diagnostic = Diagnostic::new("failed to parse identifier, expected identifier", span);
+
+        assert_eq!(
+            diagnostic.render(source),
+            "error: failed to parse identifier, expected identifier\n \
+             --> main.d:2:16\n  \
+             |\n\
+             2 | let y: i32 = x +;\n  \
+             |                ^"
+        );
+    }
+}
@@ -0,0 +1,610 @@
+use crate::{
+    diagnostics::{Diagnostic, Result},
+    span::{Position, Span},
+};
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter, Result as FmtResult},
+    sync::Arc,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Identifier,
+    Integer,
+
+    Let,
+    If,
+    Else,
+    While,
+    For,
+    In,
+    Fn,
+    Return,
+    Const,
+    Break,
+    Continue,
+
+    LeftBrace,
+    RightBrace,
+    LeftParen,
+    RightParen,
+    Colon,
+    Semicolon,
+    Comma,
+    Arrow,
+    DotDot,
+    DotDotEqual,
+
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Bang,
+    Assign,
+    PlusAssign,
+    MinusAssign,
+    StarAssign,
+    SlashAssign,
+    PercentAssign,
+    EqualEqual,
+    BangEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    AndAnd,
+    OrOr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+/// Output of the lexer, owning every token of a source file together with
+/// the diagnostics reported while lexing and parsing it.
+pub struct Tokens<'a> {
+    tokens: Vec<Token<'a>>,
+    end: Span,
+    diagnostics: RefCell<Vec<Diagnostic>>,
+}
+
+/// Cursor over the tokens of a source file. Advancing it only moves a slice
+/// forward, so the parser can hand it around by value without ever copying
+/// the remaining input.
+#[derive(Clone, Copy)]
+pub struct TokenStream<'a> {
+    tokens: &'a [Token<'a>],
+    end: &'a Span,
+    diagnostics: &'a RefCell<Vec<Diagnostic>>,
+}
+
+pub struct Lexer<'a> {
+    file: Arc<str>,
+    source: &'a str,
+    position: Position,
+}
+
+impl TokenKind {
+    pub fn is_assignment(self) -> bool {
+        matches!(
+            self,
+            Self::Assign
+                | Self::PlusAssign
+                | Self::MinusAssign
+                | Self::StarAssign
+                | Self::SlashAssign
+                | Self::PercentAssign
+        )
+    }
+
+    fn keyword(text: &str) -> Option<Self> {
+        match text {
+            "let" => Some(Self::Let),
+            "if" => Some(Self::If),
+            "else" => Some(Self::Else),
+            "while" => Some(Self::While),
+            "for" => Some(Self::For),
+            "in" => Some(Self::In),
+            "fn" => Some(Self::Fn),
+            "return" => Some(Self::Return),
+            "const" => Some(Self::Const),
+            "break" => Some(Self::Break),
+            "continue" => Some(Self::Continue),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Tokens<'a> {
+    pub fn stream(&self) -> TokenStream<'_> {
+        TokenStream {
+            tokens: &self.tokens,
+            end: &self.end,
+            diagnostics: &self.diagnostics,
+        }
+    }
+
+    /// Every diagnostic reported so far, in source order.
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.diagnostics.into_inner();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start.offset);
+        diagnostics
+    }
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn peek(&self) -> Option<&'a Token<'a>> {
+        self.tokens.first()
+    }
+
+    pub fn peek_kind(&self) -> Option<TokenKind> {
+        self.peek().map(|token| token.kind)
+    }
+
+    pub fn peek_nth_kind(&self, n: usize) -> Option<TokenKind> {
+        self.tokens.get(n).map(|token| token.kind)
+    }
+
+    pub fn next_token(&mut self) -> Option<&'a Token<'a>> {
+        let (token, remainder) = self.tokens.split_first()?;
+        self.tokens = remainder;
+        Some(token)
+    }
+
+    /// Span of the next token, or of the end of the input once every token has
+    /// been consumed.
+    pub fn span(&self) -> Span {
+        self.peek()
+            .map_or_else(|| self.end.clone(), |token| token.span.clone())
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(message, self.span())
+    }
+
+    /// Records a diagnostic without aborting the parse, for callers that can
+    /// recover and keep looking for further errors.
+    pub fn report(&self, diagnostic: Diagnostic) {
+        self.diagnostics.borrow_mut().push(diagnostic);
+    }
+
+    /// Consumes the next token if it has the given kind, otherwise fails with
+    /// "failed to parse <context>, expected <kind>".
+    pub fn expect(&mut self, kind: TokenKind, context: &str) -> Result<&'a Token<'a>> {
+        if self.peek_kind() == Some(kind) {
+            Ok(self.next_token().unwrap())
+        } else {
+            Err(self.error(format!("failed to parse {}, expected {}", context, kind)))
+        }
+    }
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(file: &str, source: &'a str) -> Self {
+        Self {
+            file: file.into(),
+            source,
+            position: Position::new(),
+        }
+    }
+
+    /// Splits the source into tokens. Unexpected characters are reported and
+    /// skipped, so the result can still be parsed for further errors.
+    pub fn tokenize(mut self) -> Tokens<'a> {
+        let mut tokens = Vec::new();
+        let mut diagnostics = Vec::new();
+        loop {
+            match self.next_token() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => break,
+                Err(diagnostic) => diagnostics.push(diagnostic),
+            }
+        }
+
+        let end = self.span_from(self.position);
+        Tokens {
+            tokens,
+            end,
+            diagnostics: RefCell::new(diagnostics),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position.offset..].chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.source[self.position.offset..].chars().nth(1)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position.advance(c);
+        Some(c)
+    }
+
+    fn eat_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position.offset;
+        while self.peek().is_some_and(&predicate) {
+            self.bump();
+        }
+        &self.source[start..self.position.offset]
+    }
+
+    fn span_from(&self, start: Position) -> Span {
+        Span::new(self.file.clone(), start, self.position)
+    }
+
+    /// Skips whitespace, `//` line comments and `/* */` block comments, which
+    /// may be nested.
+    fn skip_trivia(&mut self) -> Result<()> {
+        loop {
+            self.eat_while(char::is_whitespace);
+
+            match (self.peek(), self.peek_second()) {
+                (Some('/'), Some('/')) => {
+                    self.eat_while(|c| c != '\n');
+                }
+                (Some('/'), Some('*')) => {
+                    let start = self.position;
+                    self.bump();
+                    self.bump();
+
+                    let mut depth = 1;
+                    while depth > 0 {
+                        match (self.bump(), self.peek()) {
+                            (Some('/'), Some('*')) => {
+                                self.bump();
+                                depth += 1;
+                            }
+                            (Some('*'), Some('/')) => {
+                                self.bump();
+                                depth -= 1;
+                            }
+                            (Some(_), _) => {}
+                            (None, _) => {
+                                return Err(Diagnostic::new(
+                                    "unterminated block comment",
+                                    self.span_from(start),
+                                ));
+                            }
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>> {
+        self.skip_trivia()?;
+
+        let start = self.position;
+        let Some(c) = self.bump() else {
+            return Ok(None);
+        };
+
+        let kind = match c {
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                self.eat_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                TokenKind::keyword(&self.source[start.offset..self.position.offset])
+                    .unwrap_or(TokenKind::Identifier)
+            }
+            c if c.is_ascii_digit() => {
+                self.eat_while(|c| c.is_ascii_digit());
+                TokenKind::Integer
+            }
+            '{' => TokenKind::LeftBrace,
+            '}' => TokenKind::RightBrace,
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            ':' => TokenKind::Colon,
+            ';' => TokenKind::Semicolon,
+            ',' => TokenKind::Comma,
+            '.' if self.peek() == Some('.') => {
+                self.bump();
+                if self.peek() == Some('=') {
+                    self.bump();
+                    TokenKind::DotDotEqual
+                } else {
+                    TokenKind::DotDot
+                }
+            }
+            '+' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::PlusAssign
+            }
+            '+' => TokenKind::Plus,
+            '-' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::MinusAssign
+            }
+            '-' if self.peek() == Some('>') => {
+                self.bump();
+                TokenKind::Arrow
+            }
+            '-' => TokenKind::Minus,
+            '*' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::StarAssign
+            }
+            '*' => TokenKind::Star,
+            '/' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::SlashAssign
+            }
+            '/' => TokenKind::Slash,
+            '%' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::PercentAssign
+            }
+            '%' => TokenKind::Percent,
+            '!' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::BangEqual
+            }
+            '!' => TokenKind::Bang,
+            '=' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::EqualEqual
+            }
+            '=' => TokenKind::Assign,
+            '<' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::LessEqual
+            }
+            '<' => TokenKind::Less,
+            '>' if self.peek() == Some('=') => {
+                self.bump();
+                TokenKind::GreaterEqual
+            }
+            '>' => TokenKind::Greater,
+            '&' if self.peek() == Some('&') => {
+                self.bump();
+                TokenKind::AndAnd
+            }
+            '|' if self.peek() == Some('|') => {
+                self.bump();
+                TokenKind::OrOr
+            }
+            c => {
+                return Err(Diagnostic::new(
+                    format!("unexpected character '{}'", c),
+                    self.span_from(start),
+                ));
+            }
+        };
+
+        Ok(Some(Token {
+            kind,
+            text: &self.source[start.offset..self.position.offset],
+            span: self.span_from(start),
+        }))
+    }
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let text = match self {
+            Self::Identifier => "identifier",
+            Self::Integer => "integer",
+            Self::Let => "'let'",
+            Self::If => "'if'",
+            Self::Else => "'else'",
+            Self::While => "'while'",
+            Self::For => "'for'",
+            Self::In => "'in'",
+            Self::Fn => "'fn'",
+            Self::Return => "'return'",
+            Self::Const => "'const'",
+            Self::Break => "'break'",
+            Self::Continue => "'continue'",
+            Self::LeftBrace => "'{'",
+            Self::RightBrace => "'}'",
+            Self::LeftParen => "'('",
+            Self::RightParen => "')'",
+            Self::Colon => "':'",
+            Self::Semicolon => "';'",
+            Self::Comma => "','",
+            Self::DotDot => "'..'",
+            Self::DotDotEqual => "'..='",
+            Self::Arrow => "'->'",
+            Self::Plus => "'+'",
+            Self::Minus => "'-'",
+            Self::Star => "'*'",
+            Self::Slash => "'/'",
+            Self::Percent => "'%'",
+            Self::Bang => "'!'",
+            Self::Assign => "'='",
+            Self::PlusAssign => "'+='",
+            Self::MinusAssign => "'-='",
+            Self::StarAssign => "'*='",
+            Self::SlashAssign => "'/='",
+            Self::PercentAssign => "'%='",
+            Self::EqualEqual => "'=='",
+            Self::BangEqual => "'!='",
+            Self::Less => "'<'",
+            Self::Greater => "'>'",
+            Self::LessEqual => "'<='",
+            Self::GreaterEqual => "'>='",
+            Self::AndAnd => "'&&'",
+            Self::OrOr => "'||'",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        let tokens = Lexer::new("test", source).tokenize();
+        let mut tokens = tokens.stream();
+        let mut kinds = Vec::new();
+        while let Some(token) = tokens.next_token() {
+            kinds.push(token.kind);
+        }
+        kinds
+    }
+
+    #[test]
+    fn test_keywords() {
+        assert_eq!(
+            kinds("let if else while for in fn return const break continue"),
+            vec![
+                TokenKind::Let,
+                TokenKind::If,
+                TokenKind::Else,
+                TokenKind::While,
+                TokenKind::For,
+                TokenKind::In,
+                TokenKind::Fn,
+                TokenKind::Return,
+                TokenKind::Const,
+                TokenKind::Break,
+                TokenKind::Continue,
+            ]
+        );
+        assert_eq!(
+            kinds("lenght iffy elsewhere whiles _let fnord returns constant breaks format inner"),
+            vec![TokenKind::Identifier; 11]
+        );
+        assert_eq!(
+            kinds("fn f(a: i32, b: i32) -> i32"),
+            vec![
+                TokenKind::Fn,
+                TokenKind::Identifier,
+                TokenKind::LeftParen,
+                TokenKind::Identifier,
+                TokenKind::Colon,
+                TokenKind::Identifier,
+                TokenKind::Comma,
+                TokenKind::Identifier,
+                TokenKind::Colon,
+                TokenKind::Identifier,
+                TokenKind::RightParen,
+                TokenKind::Arrow,
+                TokenKind::Identifier,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(
+            kinds("a<=b>=c<d>e&&f||!g=12==h!=i%2"),
+            vec![
+                TokenKind::Identifier,
+                TokenKind::LessEqual,
+                TokenKind::Identifier,
+                TokenKind::GreaterEqual,
+                TokenKind::Identifier,
+                TokenKind::Less,
+                TokenKind::Identifier,
+                TokenKind::Greater,
+                TokenKind::Identifier,
+                TokenKind::AndAnd,
+                TokenKind::Identifier,
+                TokenKind::OrOr,
+                TokenKind::Bang,
+                TokenKind::Identifier,
+                TokenKind::Assign,
+                TokenKind::Integer,
+                TokenKind::EqualEqual,
+                TokenKind::Identifier,
+                TokenKind::BangEqual,
+                TokenKind::Identifier,
+                TokenKind::Percent,
+                TokenKind::Integer,
+            ]
+        );
+        assert_eq!(
+            kinds("0..n..=m"),
+            vec![
+                TokenKind::Integer,
+                TokenKind::DotDot,
+                TokenKind::Identifier,
+                TokenKind::DotDotEqual,
+                TokenKind::Identifier,
+            ]
+        );
+        let diagnostics = Lexer::new("test", "a & b | c")
+            .tokenize()
+            .into_diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        assert_eq!(
+            kinds("a += b -= c *= d /= e %= f"),
+            vec![
+                TokenKind::Identifier,
+                TokenKind::PlusAssign,
+                TokenKind::Identifier,
+                TokenKind::MinusAssign,
+                TokenKind::Identifier,
+                TokenKind::StarAssign,
+                TokenKind::Identifier,
+                TokenKind::SlashAssign,
+                TokenKind::Identifier,
+                TokenKind::PercentAssign,
+                TokenKind::Identifier,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let tokens = Lexer::new("test", "let x\n  = 12;").tokenize();
+        let mut tokens = tokens.stream();
+        let positions = std::iter::from_fn(|| tokens.next_token())
+            .map(|token| (token.span.start.line, token.span.start.column))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(1, 1), (1, 5), (2, 3), (2, 5), (2, 7)]);
+        assert_eq!(tokens.span().start.column, 8);
+
+        let tokens = Lexer::new("test", "x\n  $ y").tokenize();
+        assert_eq!(tokens.stream().peek().unwrap().text, "x");
+        let diagnostics = tokens.into_diagnostics();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test:2:3: unexpected character '$'"
+        );
+
+        // Identifiers are ASCII only.
+        let tokens = Lexer::new("test", "café").tokenize();
+        assert_eq!(tokens.stream().peek().unwrap().text, "caf");
+        let diagnostics = tokens.into_diagnostics();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test:1:4: unexpected character 'é'"
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_eq!(
+            kinds("a // b c\n/* d /* e */ f */ g / h"),
+            vec![
+                TokenKind::Identifier,
+                TokenKind::Identifier,
+                TokenKind::Slash,
+                TokenKind::Identifier,
+            ]
+        );
+
+        let tokens = Lexer::new("test", "a /* b /* c */").tokenize();
+        let diagnostics = tokens.into_diagnostics();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test:1:3: unterminated block comment"
+        );
+    }
+}
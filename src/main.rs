@@ -1,35 +1,101 @@
 use crate::{
+    ast::AstNode,
     compiler::{NodeCompiler, llvmcompiler::LlvmCompiler},
-    parser::{Parser, Start},
-    peek::StringUtils,
+    diagnostics::Diagnostic,
+    lexer::Lexer,
+    options::{Mode, Options},
 };
 use anyhow::{Result, anyhow};
-use inkwell::context::Context;
+use inkwell::{OptimizationLevel, context::Context};
+use std::{path::Path, process::Command};
 
 mod ast;
 mod compiler;
+mod diagnostics;
+mod lexer;
+mod options;
 mod parser;
-mod peek;
+mod span;
 
 fn main() -> Result<()> {
-    let args = std::env::args().collect::<Vec<String>>();
-    if args.len() != 2 {
-        return Err(anyhow!("Usage: cargo build <file>"));
-    }
+    let options = Options::parse(std::env::args().skip(1))?;
 
-    let file = &args[1];
-    let mut input = std::fs::read_to_string(file).unwrap().trim_mut();
+    let file = &options.file;
+    let input = std::fs::read_to_string(file)?;
+    let program = match parse(file, &input) {
+        Ok(program) => program,
+        Err(diagnostics) => abort(&diagnostics, &input),
+    };
 
     let context = Context::create();
     let mut compiler = LlvmCompiler::new(file, &context);
+    if options.debug_info {
+        let optimized = options.optimization != OptimizationLevel::None;
+        compiler.enable_debug_info(Path::new(file), optimized)?;
+    }
+    // Errors in the program itself carry a span and are shown like syntax errors;
+    // anything else is a failure of the compiler or its environment.
+    if let Err(error) = program.accept(&mut compiler) {
+        match error.downcast::<Diagnostic>() {
+            Ok(diagnostic) => abort(&[diagnostic], &input),
+            Err(error) => return Err(error),
+        }
+    }
+    let mut ir = compiler.compile()?;
+    if options.optimization != OptimizationLevel::None {
+        ir = compiler.optimize(options.optimization)?;
+    }
+
+    if options.mode == Mode::Run {
+        println!("{}", compiler.run(options.optimization)?);
+        return Ok(());
+    }
+
+    std::fs::write(format!("{}.ll", file), ir)?;
 
-    while !input.is_empty() {
-        let (expression, remainder) = Start::parse(input.trim_mut())?;
-        println!("Current line parsed into: {}", expression);
-        //expression.accept(&mut compiler)?;
-        //let ir = compiler.compile()?;
-        //std::fs::write(format!("{}.ll", file), ir)?;
-        input = remainder.trim_mut();
+    let object = format!("{}.o", file);
+    compiler.emit(
+        Path::new(&object),
+        Path::new(&format!("{}.s", file)),
+        options.optimization,
+    )?;
+    link(&object, &format!("{}.out", file))
+}
+
+/// Prints `diagnostics` with the source lines they point at and exits.
+fn abort(diagnostics: &[Diagnostic], input: &str) -> ! {
+    for diagnostic in diagnostics {
+        eprintln!("{}\n", diagnostic.render(input));
     }
+    eprintln!("aborting due to {} error(s)", diagnostics.len());
+    std::process::exit(1);
+}
+
+/// Links an object file into an executable with the system C compiler driver, which knows
+/// where the C runtime and startup files live.
+fn link(object: &str, executable: &str) -> Result<()> {
+    let status = Command::new("cc")
+        .arg(object)
+        .arg("-o")
+        .arg(executable)
+        .status()
+        .map_err(|error| anyhow!("Failed to run linker `cc`: {}", error))?;
+
+    if !status.success() {
+        return Err(anyhow!("Linker `cc` failed with {}", status));
+    }
+
     Ok(())
 }
+
+fn parse(file: &str, input: &str) -> Result<Box<dyn AstNode>, Vec<Diagnostic>> {
+    let tokens = Lexer::new(file, input).tokenize();
+    let program = parser::parse(tokens.stream());
+
+    let diagnostics = tokens.into_diagnostics();
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    Ok(program)
+}
@@ -0,0 +1,115 @@
+use anyhow::{Result, anyhow};
+use inkwell::OptimizationLevel;
+
+const USAGE: &str = "Usage: compiler [run] [-O0|-O1|-O2|-O3] [-g] <file>";
+
+/// What the driver does with the compiled program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Writes the IR, assembly, object file and a linked executable next to the source.
+    Build,
+    /// JIT-compiles the program in-process and prints the value returned by `main`.
+    Run,
+}
+
+/// Command line options of the driver.
+pub struct Options {
+    pub mode: Mode,
+    pub file: String,
+    pub optimization: OptimizationLevel,
+    /// Whether to emit DWARF debug information.
+    pub debug_info: bool,
+}
+
+impl Options {
+    /// Parses the arguments following the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter().peekable();
+        let mode = match args.peek().map(String::as_str) {
+            Some("run") => {
+                args.next();
+                Mode::Run
+            }
+            _ => Mode::Build,
+        };
+
+        let mut file = None;
+        let mut optimization = OptimizationLevel::None;
+        let mut debug_info = false;
+
+        for arg in args {
+            match arg.as_str() {
+                "-O0" => optimization = OptimizationLevel::None,
+                "-O1" => optimization = OptimizationLevel::Less,
+                "-O2" => optimization = OptimizationLevel::Default,
+                "-O3" => optimization = OptimizationLevel::Aggressive,
+                "-g" => debug_info = true,
+                flag if flag.starts_with('-') => {
+                    return Err(anyhow!("Unknown option `{}`\n{}", flag, USAGE));
+                }
+                _ if file.is_some() => return Err(anyhow!(USAGE)),
+                _ => file = Some(arg),
+            }
+        }
+
+        Ok(Self {
+            mode,
+            file: file.ok_or_else(|| anyhow!(USAGE))?,
+            optimization,
+            debug_info,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_optimization_levels() {
+        let options = parse(&["main.d"]).unwrap();
+        assert_eq!(options.mode, Mode::Build);
+        assert_eq!(options.file, "main.d");
+        assert_eq!(options.optimization, OptimizationLevel::None);
+
+        let options = parse(&["-O2", "main.d"]).unwrap();
+        assert_eq!(options.optimization, OptimizationLevel::Default);
+
+        let options = parse(&["main.d", "-O3"]).unwrap();
+        assert_eq!(options.optimization, OptimizationLevel::Aggressive);
+        assert!(!options.debug_info);
+    }
+
+    #[test]
+    fn test_debug_info() {
+        let options = parse(&["-g", "-O1", "main.d"]).unwrap();
+        assert!(options.debug_info);
+        assert_eq!(options.optimization, OptimizationLevel::Less);
+    }
+
+    #[test]
+    fn test_run_mode() {
+        let options = parse(&["run", "-O1", "main.d"]).unwrap();
+        assert_eq!(options.mode, Mode::Run);
+        assert_eq!(options.file, "main.d");
+        assert_eq!(options.optimization, OptimizationLevel::Less);
+
+        // Only the first argument selects the mode, so a file may still be called `run`.
+        let options = parse(&["run", "run"]).unwrap();
+        assert_eq!(options.mode, Mode::Run);
+        assert_eq!(options.file, "run");
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["a.d", "b.d"]).is_err());
+
+        let error = parse(&["-O4", "main.d"]).err().unwrap();
+        assert!(error.to_string().starts_with("Unknown option `-O4`"));
+    }
+}
@@ -1,27 +1,30 @@
 use crate::{
     ast::{AstNode, BinaryAstNode, arithmetic::*},
+    diagnostics::{Diagnostic, Result},
+    lexer::{TokenKind, TokenStream},
     parser::{Expression, Not, Parser, Term, utils::parse_binary_op},
-    peek::StringUtils,
 };
-use anyhow::{Result, anyhow};
 
 impl Parser for Expression {
     type TNext = Term;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        parse_binary_op::<i32, Self::TNext, _>(
-            string.trim_mut(),
-            vec!['+', '-'].as_slice(),
-            |left, mut string| match string.remove(0) {
-                '+' => {
-                    let (right, remainder) = Self::TNext::parse(string)?;
-                    Ok((Addition::new(left, right), remainder.trim_mut()))
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        parse_binary_op::<Self::TNext, _>(
+            tokens,
+            &[TokenKind::Plus, TokenKind::Minus],
+            |left, operator, tokens| match operator.kind {
+                TokenKind::Plus => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((Addition::new(left, right), remainder))
                 }
-                '-' => {
-                    let (right, remainder) = Self::TNext::parse(string)?;
-                    Ok((Subtraction::new(left, right), remainder.trim_mut()))
+                TokenKind::Minus => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((Subtraction::new(left, right), remainder))
                 }
-                _ => return Err(anyhow!("expected '|'")),
+                _ => Err(Diagnostic::new(
+                    "expected '+' or '-'",
+                    operator.span.clone(),
+                )),
             },
         )
     }
@@ -30,20 +33,27 @@ impl Parser for Expression {
 impl Parser for Term {
     type TNext = Not;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        parse_binary_op::<i32, Self::TNext, _>(
-            string.trim_mut(),
-            vec!['*', '/'].as_slice(),
-            |left, mut string| match string.remove(0) {
-                '*' => {
-                    let (right, remainder) = Self::TNext::parse(string)?;
-                    Ok((Multiplication::new(left, right), remainder.trim_mut()))
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        parse_binary_op::<Self::TNext, _>(
+            tokens,
+            &[TokenKind::Star, TokenKind::Slash, TokenKind::Percent],
+            |left, operator, tokens| match operator.kind {
+                TokenKind::Star => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((Multiplication::new(left, right), remainder))
                 }
-                '/' => {
-                    let (right, remainder) = Self::TNext::parse(string)?;
-                    Ok((Division::new(left, right), remainder.trim_mut()))
+                TokenKind::Slash => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((Division::new(left, right), remainder))
                 }
-                _ => return Err(anyhow!("expected '|'")),
+                TokenKind::Percent => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((Remainder::new(left, right), remainder))
+                }
+                _ => Err(Diagnostic::new(
+                    "expected '*', '/' or '%'",
+                    operator.span.clone(),
+                )),
             },
         )
     }
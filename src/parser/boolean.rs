@@ -1,47 +1,65 @@
 use crate::{
-    ast::{AstNode, BinaryAstNode, boolean::*},
-    parser::{And, Expression, Factor, Not, Or, Parser, Relation, Term, utils::parse_binary_op},
-    peek::StringUtils,
+    ast::{AstNode, BinaryAstNode, arithmetic::Negation, boolean::*},
+    diagnostics::{Diagnostic, Result},
+    lexer::{TokenKind, TokenStream},
+    parser::{
+        And, Equality, Expression, Factor, Not, Or, Parser, Relation,
+        utils::{parse_binary_op, parse_integer},
+    },
 };
-use anyhow::{Result, anyhow};
 
 impl Parser for Or {
     type TNext = And;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        parse_binary_op::<i32, Self::TNext, _>(
-            string.trim_mut(),
-            vec!['|'].as_slice(),
-            |left, mut string| {
-                string.remove(0);
-                match string.remove(0) {
-                    '|' => {
-                        let (right, remainder) = Self::TNext::parse(string)?;
-                        Ok((LogicalOr::new(left, right), remainder.trim_mut()))
-                    }
-                    _ => return Err(anyhow!("expected '|'")),
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        parse_binary_op::<Self::TNext, _>(tokens, &[TokenKind::OrOr], |left, operator, tokens| {
+            match operator.kind {
+                TokenKind::OrOr => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((LogicalOr::new(left, right), remainder))
                 }
-            },
-        )
+                _ => Err(Diagnostic::new("expected '||'", operator.span.clone())),
+            }
+        })
     }
 }
 
 impl Parser for And {
+    type TNext = Equality;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        parse_binary_op::<Self::TNext, _>(tokens, &[TokenKind::AndAnd], |left, operator, tokens| {
+            match operator.kind {
+                TokenKind::AndAnd => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((LogicalAnd::new(left, right), remainder))
+                }
+                _ => Err(Diagnostic::new("expected '&&'", operator.span.clone())),
+            }
+        })
+    }
+}
+
+impl Parser for Equality {
     type TNext = Relation;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        parse_binary_op::<i32, Self::TNext, _>(
-            string.trim_mut(),
-            vec!['&'].as_slice(),
-            |left, mut string| {
-                string.remove(0);
-                match string.remove(0) {
-                    '&' => {
-                        let (right, remainder) = Self::TNext::parse(string)?;
-                        Ok((LogicalAnd::new(left, right), remainder.trim_mut()))
-                    }
-                    _ => return Err(anyhow!("expected '|'")),
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        parse_binary_op::<Self::TNext, _>(
+            tokens,
+            &[TokenKind::EqualEqual, TokenKind::BangEqual],
+            |left, operator, tokens| match operator.kind {
+                TokenKind::EqualEqual => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((Equal::new(left, right), remainder))
                 }
+                TokenKind::BangEqual => {
+                    let (right, remainder) = Self::TNext::parse(tokens)?;
+                    Ok((NotEqual::new(left, right), remainder))
+                }
+                _ => Err(Diagnostic::new(
+                    "expected '==' or '!='",
+                    operator.span.clone(),
+                )),
             },
         )
     }
@@ -50,34 +68,27 @@ impl Parser for And {
 impl Parser for Relation {
     type TNext = Expression;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        parse_binary_op::<i32, Self::TNext, _>(
-            string.trim_mut(),
-            vec!['>', '<'].as_slice(),
-            |left, mut string| match string.remove(0) {
-                '>' => {
-                    if string.peek().is_some_and(|c| c == '=') {
-                        string.remove(0);
-
-                        let (right, remainder) = Expression::parse(string.trim_mut())?;
-                        Ok((GreaterEqual::new(left, right), remainder.trim_mut()))
-                    } else {
-                        let (right, remainder) = Term::parse(string.trim_mut())?;
-                        Ok((Greater::new(left, right), remainder.trim_mut()))
-                    }
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        parse_binary_op::<Self::TNext, _>(
+            tokens,
+            &[
+                TokenKind::Less,
+                TokenKind::Greater,
+                TokenKind::LessEqual,
+                TokenKind::GreaterEqual,
+            ],
+            |left, operator, tokens| {
+                let (right, remainder) = Self::TNext::parse(tokens)?;
+                match operator.kind {
+                    TokenKind::Less => Ok((Less::new(left, right), remainder)),
+                    TokenKind::Greater => Ok((Greater::new(left, right), remainder)),
+                    TokenKind::LessEqual => Ok((LessEqual::new(left, right), remainder)),
+                    TokenKind::GreaterEqual => Ok((GreaterEqual::new(left, right), remainder)),
+                    _ => Err(Diagnostic::new(
+                        "expected '<', '>', '<=' or '>='",
+                        operator.span.clone(),
+                    )),
                 }
-                '<' => {
-                    if string.peek().is_some_and(|c| c == '=') {
-                        string.remove(0);
-
-                        let (right, remainder) = Expression::parse(string.trim_mut())?;
-                        Ok((LessEqual::new(left, right), remainder.trim_mut()))
-                    } else {
-                        let (right, remainder) = Term::parse(string.trim_mut())?;
-                        Ok((Less::new(left, right), remainder.trim_mut()))
-                    }
-                }
-                _ => return Err(anyhow!("expected '>' or '<'")),
             },
         )
     }
@@ -86,16 +97,34 @@ impl Parser for Relation {
 impl Parser for Not {
     type TNext = Factor;
 
-    fn parse(mut string: String) -> Result<(Box<dyn AstNode>, String)> {
-        if let Some(c) = string.peek()
-            && c == '!'
-        {
-            string.remove(0);
-            let (value, remainder) = Factor::parse(string)?;
+    fn parse(mut tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        match tokens.peek_kind() {
+            Some(TokenKind::Bang) => {
+                let operator = tokens.next_token().unwrap();
+                let (value, remainder) = Self::parse(tokens)?;
 
-            return Ok((Box::new(LogicalNot { value }), remainder));
-        }
+                let span = operator.span.to(value.span());
+                Ok((Box::new(LogicalNot { value, span }), remainder))
+            }
+            Some(TokenKind::Minus) => {
+                let operator = tokens.next_token().unwrap();
+
+                // Fold the sign into literals so that i32::MIN, whose magnitude does not
+                // fit in an i32, can still be written.
+                if tokens.peek_kind() == Some(TokenKind::Integer) {
+                    let literal = tokens.next_token().unwrap();
+                    let span = operator.span.to(&literal.span);
+                    let integer = parse_integer(&format!("-{}", literal.text), span)?;
 
-        Factor::parse(string.trim_mut())
+                    return Ok((integer, tokens));
+                }
+
+                let (value, remainder) = Self::parse(tokens)?;
+
+                let span = operator.span.to(value.span());
+                Ok((Box::new(Negation { value, span }), remainder))
+            }
+            _ => Self::TNext::parse(tokens),
+        }
     }
 }
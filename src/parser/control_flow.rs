@@ -1,122 +1,236 @@
-use anyhow::{Result, anyhow};
-
 use crate::{
     ast::{
         AstNode, GenericAstNode,
-        control_flow::{Block as AstBlock, If as AstIf, While as AstWhile},
+        control_flow::{
+            Block as AstBlock, Break as AstBreak, Continue as AstContinue, For as AstFor,
+            If as AstIf, While as AstWhile,
+        },
+    },
+    diagnostics::{Diagnostic, Result},
+    lexer::{TokenKind, TokenStream},
+    parser::{
+        Assignment, Block, Break, Continue, Declaration, For, If, Instruction, Or, ParseContext,
+        Parser, Return, While,
+        utils::{parse_identifier, parse_instructions},
     },
-    parser::{Block, Declaration, Expression, If, Instruction, Or, Parser, While},
-    peek::StringUtils,
 };
 
 impl Parser for Instruction {
     type TNext = Or;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        let string = string.trim_mut();
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        Self::parse_in(tokens, ParseContext::default())
+    }
+}
 
-        match string.peek() {
-            Some('l') => Declaration::parse(string),
-            Some('{') => Block::parse(string),
-            Some('i') => If::parse(string),
-            Some('w') => While::parse(string),
-            _ => Or::parse(string),
+impl Instruction {
+    /// Parses an instruction within `context`. The `Parser` impls of the
+    /// instructions start outside of any loop.
+    pub fn parse_in(
+        tokens: TokenStream<'_>,
+        context: ParseContext,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        match tokens.peek_kind() {
+            Some(TokenKind::Let) => Declaration::parse(tokens),
+            Some(TokenKind::Identifier)
+                if tokens
+                    .peek_nth_kind(1)
+                    .is_some_and(TokenKind::is_assignment) =>
+            {
+                Assignment::parse(tokens)
+            }
+            Some(TokenKind::Return) => Return::parse(tokens),
+            Some(TokenKind::Break) => Break::parse_in(tokens, context),
+            Some(TokenKind::Continue) => Continue::parse_in(tokens, context),
+            Some(TokenKind::LeftBrace) => Block::parse_in(tokens, context),
+            Some(TokenKind::If) => If::parse_in(tokens, context),
+            Some(TokenKind::While) => While::parse_in(tokens, context),
+            Some(TokenKind::For) => For::parse_in(tokens, context),
+            _ => {
+                let (expression, mut tokens) = Or::parse(tokens)?;
+                // Only the last instruction of a block may leave out the `;`.
+                if tokens.peek_kind() != Some(TokenKind::RightBrace) {
+                    tokens.expect(TokenKind::Semicolon, "expression statement")?;
+                }
+
+                Ok((expression, tokens))
+            }
         }
     }
 }
 
 impl Parser for Block {
-    type TNext = If;
+    type TNext = Instruction;
 
-    fn parse(string: String) -> anyhow::Result<(Box<dyn AstNode>, String)> {
-        let mut string = string.trim_mut();
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        Self::parse_in(tokens, ParseContext::default())
+    }
+}
 
-        if !string.peek().is_some_and(|c| c == '{') {
-            return Err(anyhow!("failed parsing block, expected \'{}\'", '{'));
-        }
-        string.remove(0);
-        let mut block = AstBlock::new();
-        let mut string = string.trim_mut();
+impl Block {
+    pub fn parse_in(
+        mut tokens: TokenStream<'_>,
+        context: ParseContext,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let open = tokens.expect(TokenKind::LeftBrace, "block")?;
+        let mut block = AstBlock::new(open.span.clone());
 
-        while !string.peek().is_none_or(|c| c == '}') {
-            let (instruction, remainder) = Instruction::parse(string.trim_mut())?;
+        let (instructions, mut tokens) = parse_instructions(tokens, context);
+        for instruction in instructions {
             block.add_node(instruction);
-            string = remainder.trim_mut();
-        }
-        if !string.peek().is_some_and(|c| c == '}') {
-            return Err(anyhow!("failed parsing block, expected \'{}\'", '}'));
         }
-        string.remove(0);
+        let close = tokens.expect(TokenKind::RightBrace, "block")?;
+        block.set_span(open.span.to(&close.span));
 
-        Ok((block, string))
+        Ok((block, tokens))
     }
 }
 
 impl Parser for If {
-    type TNext = While;
+    type TNext = Block;
 
-    fn parse(string: String) -> anyhow::Result<(Box<dyn AstNode>, String)> {
-        let mut string = string.trim_mut();
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        Self::parse_in(tokens, ParseContext::default())
+    }
+}
 
-        if !string.peek().is_some_and(|c| c == 'i') {
-            return Err(anyhow!("failed parsing block, expected \'if\'"));
-        }
-        string.remove(0);
-        if !string.peek().is_some_and(|c| c == 'f') {
-            return Err(anyhow!("failed parsing block, expected \'if\'"));
-        }
-        string.remove(0);
-
-        let (expression, remainder) = Or::parse(string.trim_mut())?;
-        let (then, remainder) = Block::parse(remainder.trim_mut())?;
-        let mut string = remainder.trim_mut();
-        let (otherwise, remainder) = if string.starts_with("else") {
-            string.remove(0);
-            string.remove(0);
-            string.remove(0);
-            string.remove(0);
-
-            let (otherwise, remainder) = Block::parse(string.trim_mut())?;
+impl If {
+    pub fn parse_in(
+        mut tokens: TokenStream<'_>,
+        context: ParseContext,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::If, "if")?;
+
+        let (expression, remainder) = Or::parse(tokens)?;
+        let (then, mut tokens) = Block::parse_in(remainder, context)?;
+        let (otherwise, tokens) = if tokens.peek_kind() == Some(TokenKind::Else) {
+            tokens.next_token();
+
+            let (otherwise, remainder) = Block::parse_in(tokens, context)?;
             (Some(otherwise), remainder)
         } else {
-            (None, string)
+            (None, tokens)
         };
 
-        let node = AstIf::new(expression, then, otherwise);
-        Ok((node, remainder))
+        let span = keyword.span.to(otherwise.as_ref().unwrap_or(&then).span());
+        let node = AstIf::new(expression, then, otherwise, span);
+        Ok((node, tokens))
     }
 }
 
 impl Parser for While {
     type TNext = Block;
 
-    fn parse(string: String) -> anyhow::Result<(Box<dyn AstNode>, String)> {
-        let mut string = string.trim_mut();
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        Self::parse_in(tokens, ParseContext::default())
+    }
+}
 
-        if !string.peek().is_some_and(|c| c == 'w') {
-            return Err(anyhow!("failed parsing block, expected \'while\'"));
-        }
-        string.remove(0);
-        if !string.peek().is_some_and(|c| c == 'h') {
-            return Err(anyhow!("failed parsing block, expected \'while\'"));
-        }
-        string.remove(0);
-        if !string.peek().is_some_and(|c| c == 'i') {
-            return Err(anyhow!("failed parsing block, expected \'while\'"));
-        }
-        string.remove(0);
-        if !string.peek().is_some_and(|c| c == 'l') {
-            return Err(anyhow!("failed parsing block, expected \'while\'"));
+impl While {
+    pub fn parse_in(
+        mut tokens: TokenStream<'_>,
+        context: ParseContext,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::While, "while")?;
+
+        let (expression, remainder) = Or::parse(tokens)?;
+        let (block, remainder) = Block::parse_in(remainder, context.enter_loop())?;
+        let span = keyword.span.to(block.span());
+        let node = AstWhile::new(expression, block, span);
+        Ok((node, remainder))
+    }
+}
+
+impl Parser for For {
+    type TNext = Block;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        Self::parse_in(tokens, ParseContext::default())
+    }
+}
+
+impl For {
+    pub fn parse_in(
+        mut tokens: TokenStream<'_>,
+        context: ParseContext,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::For, "for")?;
+        let (variable, mut tokens) = parse_identifier(tokens)?;
+        tokens.expect(TokenKind::In, "for")?;
+
+        let (start, mut tokens) = Or::parse(tokens)?;
+        let inclusive = match tokens.peek_kind() {
+            Some(TokenKind::DotDot) => false,
+            Some(TokenKind::DotDotEqual) => true,
+            _ => return Err(tokens.error("failed to parse range, expected '..' or '..='")),
+        };
+        tokens.next_token();
+        let (end, tokens) = Or::parse(tokens)?;
+
+        let (block, tokens) = Block::parse_in(tokens, context.enter_loop())?;
+        let span = keyword.span.to(block.span());
+        let node = Box::new(AstFor {
+            variable,
+            start,
+            end,
+            inclusive,
+            block,
+            span,
+        });
+        Ok((node, tokens))
+    }
+}
+
+impl Parser for Break {
+    type TNext = Instruction;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        Self::parse_in(tokens, ParseContext::default())
+    }
+}
+
+impl Break {
+    pub fn parse_in(
+        mut tokens: TokenStream<'_>,
+        context: ParseContext,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::Break, "break")?;
+        if !context.in_loop() {
+            return Err(Diagnostic::new(
+                "'break' is only allowed inside a loop",
+                keyword.span.clone(),
+            ));
         }
-        string.remove(0);
-        if !string.peek().is_some_and(|c| c == 'e') {
-            return Err(anyhow!("failed parsing block, expected \'while\'"));
+
+        let semicolon = tokens.expect(TokenKind::Semicolon, "break")?;
+        let span = keyword.span.to(&semicolon.span);
+        Ok((Box::new(AstBreak { span }), tokens))
+    }
+}
+
+impl Parser for Continue {
+    type TNext = Instruction;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        Self::parse_in(tokens, ParseContext::default())
+    }
+}
+
+impl Continue {
+    pub fn parse_in(
+        mut tokens: TokenStream<'_>,
+        context: ParseContext,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::Continue, "continue")?;
+        if !context.in_loop() {
+            return Err(Diagnostic::new(
+                "'continue' is only allowed inside a loop",
+                keyword.span.clone(),
+            ));
         }
-        string.remove(0);
 
-        let (expression, remainder) = Or::parse(string.trim_mut())?;
-        let (block, remainder) = Block::parse(remainder.trim_mut())?;
-        let node = AstWhile::new(expression, block);
-        Ok((node, remainder))
+        let semicolon = tokens.expect(TokenKind::Semicolon, "continue")?;
+        let span = keyword.span.to(&semicolon.span);
+        Ok((Box::new(AstContinue { span }), tokens))
     }
 }
@@ -0,0 +1,105 @@
+use crate::{
+    ast::{
+        AstNode,
+        functions::{Call as AstCall, Function as AstFunction, Return as AstReturn},
+    },
+    diagnostics::Result,
+    lexer::{TokenKind, TokenStream},
+    parser::{
+        Block, Call, Function, Or, Parser, Return,
+        utils::{Type, parse_identifier, parse_type},
+    },
+};
+
+impl Parser for Function {
+    type TNext = Block;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let (function, tokens) = Self::parse_unboxed(tokens)?;
+
+        Ok((Box::new(function), tokens))
+    }
+}
+
+impl Function {
+    /// Parses a function definition; `Program` keeps them unboxed to tell its items apart.
+    pub fn parse_unboxed(mut tokens: TokenStream<'_>) -> Result<(AstFunction, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::Fn, "function")?;
+        let (name, mut tokens) = parse_identifier(tokens)?;
+
+        tokens.expect(TokenKind::LeftParen, "function")?;
+        let mut parameters = Vec::new();
+        while tokens.peek_kind() != Some(TokenKind::RightParen) {
+            if !parameters.is_empty() {
+                tokens.expect(TokenKind::Comma, "parameter list")?;
+            }
+
+            let (parameter, remainder) = parse_identifier(tokens)?;
+            tokens = remainder;
+            tokens.expect(TokenKind::Colon, "parameter")?;
+            let (Type::I32, remainder) = parse_type(tokens)?;
+            tokens = remainder;
+
+            parameters.push(parameter);
+        }
+        tokens.expect(TokenKind::RightParen, "function")?;
+
+        tokens.expect(TokenKind::Arrow, "function")?;
+        let (Type::I32, tokens) = parse_type(tokens)?;
+
+        let (body, tokens) = Block::parse(tokens)?;
+        let span = keyword.span.to(body.span());
+        let function = AstFunction {
+            name,
+            parameters,
+            body,
+            span,
+        };
+        Ok((function, tokens))
+    }
+}
+
+impl Parser for Call {
+    type TNext = Or;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let (name, mut tokens) = parse_identifier(tokens)?;
+
+        tokens.expect(TokenKind::LeftParen, "call")?;
+        let mut arguments = Vec::new();
+        while tokens.peek_kind() != Some(TokenKind::RightParen) {
+            if !arguments.is_empty() {
+                tokens.expect(TokenKind::Comma, "argument list")?;
+            }
+
+            let (argument, remainder) = Self::TNext::parse(tokens)?;
+            tokens = remainder;
+
+            arguments.push(argument);
+        }
+        let close = tokens.expect(TokenKind::RightParen, "call")?;
+
+        let span = name.span.to(&close.span);
+        let node = Box::new(AstCall {
+            name,
+            arguments,
+            span,
+        });
+        Ok((node, tokens))
+    }
+}
+
+impl Parser for Return {
+    type TNext = Or;
+
+    fn parse(mut tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::Return, "return")?;
+
+        let (value, mut tokens) = Self::TNext::parse(tokens)?;
+        let semicolon = tokens.expect(TokenKind::Semicolon, "return")?;
+
+        let span = keyword.span.to(&semicolon.span);
+        let node = Box::new(AstReturn { value, span });
+        Ok((node, tokens))
+    }
+}
@@ -1,92 +1,107 @@
 use crate::{
     ast::{
-        AstNode, BinaryAstNode,
-        lvalues::Assignment,
+        AstNode,
+        lvalues::{
+            Assignment as AstAssignment, CompoundAssignment, CompoundOperator,
+            Declaration as AstDeclaration,
+        },
         rvalues::{Integer, RValue},
     },
+    diagnostics::{Diagnostic, Result},
+    lexer::{TokenKind, TokenStream},
     parser::{
-        Declaration, Identifier, Or, Parser,
-        utils::{Type, parse_type},
+        Assignment, Declaration, Identifier, Or, Parser,
+        utils::{Type, parse_identifier, parse_type},
     },
-    peek::StringUtils,
 };
-use anyhow::Result;
 
 impl Parser for Declaration {
     type TNext = Identifier;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        let mut string = string.trim_mut();
-        if !string.peek().is_some_and(|c| c == 'l') {
-            return Err(anyhow::anyhow!(
-                "failed to parse declaration, expected 'let'"
-            ));
-        }
-        string.remove(0);
-        if !string.peek().is_some_and(|c| c == 'e') {
-            return Err(anyhow::anyhow!(
-                "failed to parse declaration, expected 'let'"
-            ));
-        }
-        string.remove(0);
-        if !string.peek().is_some_and(|c| c == 't') {
-            return Err(anyhow::anyhow!(
-                "failed to parse declaration, expected 'let'"
-            ));
-        }
-        string.remove(0);
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let (declaration, tokens) = Self::parse_unboxed(tokens)?;
 
-        let (identifier, mut remainder) = Self::TNext::parse(string)?;
-        if !remainder.peek().is_some_and(|c| c == ':') {
-            return Err(anyhow::anyhow!("failed to parse declaration, expected ':'"));
-        }
-        remainder.remove(0);
+        Ok((Box::new(declaration), tokens))
+    }
+}
+
+impl Declaration {
+    /// Parses a `let`, which `Program` also accepts as a global variable.
+    pub fn parse_unboxed(mut tokens: TokenStream<'_>) -> Result<(AstDeclaration, TokenStream<'_>)> {
+        let keyword = tokens.expect(TokenKind::Let, "declaration")?;
+
+        let (identifier, mut tokens) = parse_identifier(tokens)?;
+        tokens.expect(TokenKind::Colon, "declaration")?;
 
-        let (value, mut remainder) = match parse_type(remainder.trim_mut())? {
-            (Type::I32, remainder) => {
-                let mut string = remainder.trim_mut();
-                if string.peek().is_some_and(|c| c == '=') {
-                    string.remove(0);
-                    let (value, remaining) = Or::parse(string.trim_mut())?;
-                    string = remaining.trim_mut();
-                    (value, string)
+        let (value, mut tokens) = match parse_type(tokens)? {
+            (Type::I32, mut tokens) => {
+                if tokens.peek_kind() == Some(TokenKind::Assign) {
+                    tokens.next_token();
+                    Or::parse(tokens)?
                 } else {
-                    (Integer::default(), string)
+                    (Integer::default(identifier.span.clone()), tokens)
                 }
             }
         };
 
-        match remainder.peek() {
-            Some(';') => {
-                remainder.remove(0);
-                Ok((Assignment::new(identifier, value), remainder))
+        let semicolon = tokens.expect(TokenKind::Semicolon, "declaration")?;
+        let declaration = AstDeclaration {
+            left: identifier,
+            right: value,
+            span: keyword.span.to(&semicolon.span),
+        };
+        Ok((declaration, tokens))
+    }
+}
+
+impl Parser for Assignment {
+    type TNext = Identifier;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let (identifier, mut tokens) = parse_identifier(tokens)?;
+        let span = tokens.span();
+        let operator = match tokens.next_token().map(|token| token.kind) {
+            Some(TokenKind::Assign) => None,
+            Some(TokenKind::PlusAssign) => Some(CompoundOperator::Add),
+            Some(TokenKind::MinusAssign) => Some(CompoundOperator::Subtract),
+            Some(TokenKind::StarAssign) => Some(CompoundOperator::Multiply),
+            Some(TokenKind::SlashAssign) => Some(CompoundOperator::Divide),
+            Some(TokenKind::PercentAssign) => Some(CompoundOperator::Remainder),
+            _ => {
+                return Err(Diagnostic::new(
+                    "failed to parse assignment, expected assignment operator",
+                    span,
+                ));
             }
-            _ => Err(anyhow::anyhow!("failed to parse declaration, expected ';'")),
-        }
+        };
+
+        let (value, mut tokens) = Or::parse(tokens)?;
+        let semicolon = tokens.expect(TokenKind::Semicolon, "assignment")?;
+
+        let span = identifier.span.to(&semicolon.span);
+        let node: Box<dyn AstNode> = match operator {
+            None => Box::new(AstAssignment {
+                left: identifier,
+                right: value,
+                span,
+            }),
+            Some(operator) => Box::new(CompoundAssignment {
+                left: identifier,
+                operator,
+                right: value,
+                span,
+            }),
+        };
+        Ok((node, tokens))
     }
 }
 
 impl Parser for Identifier {
     type TNext = Or;
 
-    fn parse(string: String) -> Result<(Box<dyn crate::ast::AstNode>, String)> {
-        let mut string = string.trim_mut();
-        let mut ident_str = String::default();
-
-        if !string.peek().is_some_and(|c| c.is_alphabetic() || c == '_') {
-            return Err(anyhow::anyhow!("failed to parse identifier"));
-        }
-
-        while string
-            .peek()
-            .is_some_and(|c| c.is_alphanumeric() || c == '_')
-        {
-            ident_str.push(string.remove(0));
-        }
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let (identifier, tokens) = parse_identifier(tokens)?;
 
-        Ok((
-            Box::new(crate::ast::lvalues::Identifier { name: ident_str }),
-            string,
-        ))
+        Ok((Box::new(identifier), tokens))
     }
 }
@@ -1,26 +1,72 @@
-use crate::ast::AstNode;
-use anyhow::Result;
+use crate::{ast::AstNode, diagnostics::Result, lexer::TokenStream};
 
 pub mod arithmetic;
 pub mod boolean;
 pub mod control_flow;
+pub mod functions;
 pub mod lvalues;
+pub mod program;
 pub mod rvalues;
 mod utils;
 
 pub trait Parser {
     type TNext: Parser;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)>;
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)>;
 }
 
-pub type Start = Instruction;
+/// What the instruction parsers need to know about their surroundings beyond
+/// the tokens themselves. It is handed down by value, so leaving a construct
+/// restores the context outside of it.
+#[derive(Clone, Copy, Default)]
+pub struct ParseContext {
+    /// Number of loops enclosing the instruction being parsed.
+    loop_depth: usize,
+}
+
+impl ParseContext {
+    /// The context of a loop body, where `break` and `continue` are allowed.
+    pub fn enter_loop(self) -> Self {
+        Self {
+            loop_depth: self.loop_depth + 1,
+        }
+    }
+
+    pub fn in_loop(self) -> bool {
+        self.loop_depth > 0
+    }
+}
+
+/// Parses a whole file into its `Program`. Syntax errors are reported to the
+/// token stream rather than returned, so a single call finds all of them.
+pub fn parse(tokens: TokenStream<'_>) -> Box<dyn AstNode> {
+    let (program, _) = Program::parse_unboxed(tokens);
+    Box::new(program)
+}
 
-// <instruction> := <assignment>
+// Whitespace, `//` line comments and nestable `/* */` block comments may
+// appear between any two tokens and are discarded by the lexer.
+
+// <program> := {<item>}
+// <item> := <function>
+//         | <declaration>
+//         | <constant>
+// <constant> := 'const' <identifier> ':' <type> '=' <or> ';'
+pub struct Program;
+
+// <instruction> := <declaration>
+//                | <assignment>
+//                | <return>
+//                | <break>
+//                | <continue>
 //                | <block>
 //                | <if>
 //                | <while>
+//                | <for>
+//                | <or> ';'
 //                | <or>
+// A bare <or> without the ';' is only allowed right before the '}' closing
+// a block.
 pub struct Instruction;
 
 // <block> := '{' {<Instruction>} '}'
@@ -32,10 +78,32 @@ pub struct If;
 // <while> := 'while' <expression> <block>
 pub struct While;
 
+// <for> := 'for' <identifier> 'in' <or> ('..' | '..=') <or> <block>
+pub struct For;
+
+// <break> := 'break' ';'
+// Only allowed inside the body of a loop.
+pub struct Break;
+
+// <continue> := 'continue' ';'
+// Only allowed inside the body of a loop.
+pub struct Continue;
+
+// <function> := 'fn' <identifier> '(' [<parameter> {',' <parameter>}] ')' '->' <type> <block>
+// <parameter> := <identifier> ':' <type>
+pub struct Function;
+
+// <return> := 'return' <or> ';'
+pub struct Return;
+
 // <declaration> = "let" <identifier>: <type> ["=" <or>] ";"
 // <type> = "i32"
 pub struct Declaration;
 
+// <assignment> = <identifier> <assign-op> <or> ";"
+// <assign-op> = "=" | "+=" | "-=" | "*=" | "/=" | "%="
+pub struct Assignment;
+
 // <identifier> = [a-zA-Z_][a-zA-Z0-9_]*
 pub struct Identifier;
 
@@ -43,10 +111,15 @@ pub struct Identifier;
 //       | <or>"||"<and>
 pub struct Or;
 
-// <and> = <relation>
-//       | <and>"&&"<relation>
+// <and> = <equality>
+//       | <and>"&&"<equality>
 pub struct And;
 
+// <equality> = <relation>
+//            | <equality>"=="<relation>
+//            | <equality>"!="<relation>
+pub struct Equality;
+
 // <relation> = <expression>
 //            | <relation>"<"<expression>
 //            | <relation>">"<expression>
@@ -62,144 +135,681 @@ pub struct Expression;
 // <term> = <not>
 //        | <term>"*"<not>
 //        | <term>"/"<not>
+//        | <term>"%"<not>
 pub struct Term;
 
 // <not> = <factor>
-//       | "!"<factor>
+//       | "!"<not>
+//       | "-"<not>
 pub struct Not;
 
-// <term> = <number>
-//        | "("<instruction>")"
+// <factor> = <number>
+//          | <call>
+//          | <identifier>
+//          | "("<or>")"
 pub struct Factor;
 
+// <call> = <identifier>"(" [<or> {"," <or>}] ")"
+pub struct Call;
+
 #[cfg(test)]
 mod tests {
-    use crate::ast::{arithmetic::*, boolean::*, rvalues::*};
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        ast::{
+            arithmetic::*,
+            boolean::*,
+            lvalues::{Assignment, Declaration, Identifier},
+            rvalues::*,
+        },
+        lexer::Lexer,
+        span::Span,
+    };
 
     use super::*;
 
+    fn parse(source: &str) -> Box<dyn AstNode> {
+        let tokens = Lexer::new("test", source).tokenize();
+        let (node, remainder) = Instruction::parse(tokens.stream()).unwrap();
+        assert!(remainder.peek().is_none());
+        assert!(tokens.into_diagnostics().is_empty());
+        node
+    }
+
+    fn parse_expression(source: &str) -> Box<dyn AstNode> {
+        let tokens = Lexer::new("test", source).tokenize();
+        let (node, remainder) = Or::parse(tokens.stream()).unwrap();
+        assert!(remainder.peek().is_none());
+        assert!(tokens.into_diagnostics().is_empty());
+        node
+    }
+
+    fn parse_program(source: &str) -> Box<dyn AstNode> {
+        let tokens = Lexer::new("test", source).tokenize();
+        let program = super::parse(tokens.stream());
+        assert!(tokens.into_diagnostics().is_empty());
+        program
+    }
+
     #[test]
     fn test_arithmetic() {
-        let expression = "12 + 5 / 4".to_owned();
-        let (expression, _remainder) = Start::parse(expression).unwrap();
+        let expression = parse_expression("12 + 5 / 4");
 
         let expected = Box::new(Addition {
-            left: Box::new(Integer { value: 12 }),
+            left: Box::new(Integer {
+                value: 12,
+                span: Span::default(),
+            }),
             right: Box::new(Division {
-                left: Box::new(Integer { value: 5 }),
-                right: Box::new(Integer { value: 4 }),
+                left: Box::new(Integer {
+                    value: 5,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 4,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
+            span: Span::default(),
         });
 
         assert_eq!(format!("{}", expression), format!("{}", expected));
 
-        let expression = "(12 + 5) / 4".to_owned();
-        let (expression, _remainder) = Start::parse(expression).unwrap();
+        let expression = parse_expression("(12 + 5) / 4");
 
         let expected = Box::new(Division {
             left: Box::new(Addition {
-                left: Box::new(Integer { value: 12 }),
-                right: Box::new(Integer { value: 5 }),
+                left: Box::new(Integer {
+                    value: 12,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 5,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
-            right: Box::new(Integer { value: 4 }),
+            right: Box::new(Integer {
+                value: 4,
+                span: Span::default(),
+            }),
+            span: Span::default(),
         });
 
         assert_eq!(format!("{}", expression), format!("{}", expected));
 
-        let expression = "3 * 4 + 2 * 5".to_owned();
-        let (expression, _remainder) = Start::parse(expression).unwrap();
+        let expression = parse_expression("3 * 4 + 2 * 5");
 
         let expected = Box::new(Addition {
             left: Box::new(Multiplication {
-                left: Box::new(Integer { value: 3 }),
-                right: Box::new(Integer { value: 4 }),
+                left: Box::new(Integer {
+                    value: 3,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 4,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
             right: Box::new(Multiplication {
-                left: Box::new(Integer { value: 2 }),
-                right: Box::new(Integer { value: 5 }),
+                left: Box::new(Integer {
+                    value: 2,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 5,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
+            span: Span::default(),
         });
 
         assert_eq!(format!("{}", expression), format!("{}", expected));
 
-        let expression = "((3 + 5) * 2) - 4 / 2".to_owned();
-        let (expression, _remainder) = Start::parse(expression).unwrap();
+        let expression = parse_expression("((3 + 5) * 2) - 4 / 2");
 
         let expected = Box::new(Subtraction {
             left: Box::new(Multiplication {
                 left: Box::new(Addition {
-                    left: Box::new(Integer { value: 3 }),
-                    right: Box::new(Integer { value: 5 }),
+                    left: Box::new(Integer {
+                        value: 3,
+                        span: Span::default(),
+                    }),
+                    right: Box::new(Integer {
+                        value: 5,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 2,
+                    span: Span::default(),
                 }),
-                right: Box::new(Integer { value: 2 }),
+                span: Span::default(),
             }),
             right: Box::new(Division {
-                left: Box::new(Integer { value: 4 }),
-                right: Box::new(Integer { value: 2 }),
+                left: Box::new(Integer {
+                    value: 4,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 2,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
+            span: Span::default(),
         });
 
         assert_eq!(format!("{}", expression), format!("{}", expected));
+
+        let expression = parse_expression("a + b % 2 * c");
+        assert_eq!(
+            format!("{}", expression),
+            "Addition(a, Multiplication(Remainder(b, 2), c))"
+        );
+    }
+
+    #[test]
+    fn test_negation() {
+        let expression = parse_expression("-2147483648");
+        assert_eq!(format!("{}", expression), "-2147483648");
+
+        let expression = parse_expression("-x * -(1 - -2)");
+        assert_eq!(
+            format!("{}", expression),
+            "Multiplication(Negation(x), Negation(Subtraction(1, -2)))"
+        );
+
+        let expression = parse_expression("--x - 1");
+        assert_eq!(
+            format!("{}", expression),
+            "Subtraction(Negation(Negation(x)), 1)"
+        );
+
+        let expression = parse_expression("!!-a");
+        assert_eq!(format!("{}", expression), "Not(Not(Negation(a)))");
     }
 
     #[test]
     fn test_boolean() {
-        let expression = "3 < 5 && 2 >= 1".to_owned();
-        let (expression, _remainder) = Start::parse(expression).unwrap();
+        let expression = parse_expression("3 < 5 && 2 >= 1");
 
         let expected = Box::new(LogicalAnd {
             left: Box::new(Less {
-                left: Box::new(Integer { value: 3 }),
-                right: Box::new(Integer { value: 5 }),
+                left: Box::new(Integer {
+                    value: 3,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 5,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
             right: Box::new(GreaterEqual {
-                left: Box::new(Integer { value: 2 }),
-                right: Box::new(Integer { value: 1 }),
+                left: Box::new(Integer {
+                    value: 2,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 1,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
+            span: Span::default(),
         });
 
         assert_eq!(format!("{}", expression), format!("{}", expected));
 
-        let expression = "!(4 > 2 || 1 <= 0)".to_owned();
-        let (expression, _remainder) = Start::parse(expression).unwrap();
+        let expression = parse_expression("!(4 > 2 || 1 <= 0)");
 
         let expected = Box::new(LogicalNot {
             value: Box::new(LogicalOr {
                 left: Box::new(Greater {
-                    left: Box::new(Integer { value: 4 }),
-                    right: Box::new(Integer { value: 2 }),
+                    left: Box::new(Integer {
+                        value: 4,
+                        span: Span::default(),
+                    }),
+                    right: Box::new(Integer {
+                        value: 2,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
                 }),
                 right: Box::new(LessEqual {
-                    left: Box::new(Integer { value: 1 }),
-                    right: Box::new(Integer { value: 0 }),
+                    left: Box::new(Integer {
+                        value: 1,
+                        span: Span::default(),
+                    }),
+                    right: Box::new(Integer {
+                        value: 0,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
                 }),
+                span: Span::default(),
             }),
+            span: Span::default(),
         });
 
         assert_eq!(format!("{}", expression), format!("{}", expected));
 
-        let expression = "5 + 3 > 2 * 4 || 1 && !0".to_owned();
-        let (expression, _remainder) = Start::parse(expression).unwrap();
+        let expression = parse_expression("5 + 3 > 2 * 4 || 1 && !0");
 
         let expected = Box::new(LogicalOr {
             left: Box::new(Greater {
                 left: Box::new(Addition {
-                    left: Box::new(Integer { value: 5 }),
-                    right: Box::new(Integer { value: 3 }),
+                    left: Box::new(Integer {
+                        value: 5,
+                        span: Span::default(),
+                    }),
+                    right: Box::new(Integer {
+                        value: 3,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
                 }),
                 right: Box::new(Multiplication {
-                    left: Box::new(Integer { value: 2 }),
-                    right: Box::new(Integer { value: 4 }),
+                    left: Box::new(Integer {
+                        value: 2,
+                        span: Span::default(),
+                    }),
+                    right: Box::new(Integer {
+                        value: 4,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
                 }),
+                span: Span::default(),
             }),
             right: Box::new(LogicalAnd {
-                left: Box::new(Integer { value: 1 }),
+                left: Box::new(Integer {
+                    value: 1,
+                    span: Span::default(),
+                }),
                 right: Box::new(LogicalNot {
-                    value: Box::new(Integer { value: 0 }),
+                    value: Box::new(Integer {
+                        value: 0,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            }),
+            span: Span::default(),
+        });
+
+        assert_eq!(format!("{}", expression), format!("{}", expected));
+    }
+
+    #[test]
+    fn test_equality() {
+        let expression = parse_expression("1 < 2 == 3 >= 4 && 5 != 6");
+
+        let expected = Box::new(LogicalAnd {
+            left: Box::new(Equal {
+                left: Box::new(Less {
+                    left: Box::new(Integer {
+                        value: 1,
+                        span: Span::default(),
+                    }),
+                    right: Box::new(Integer {
+                        value: 2,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
                 }),
+                right: Box::new(GreaterEqual {
+                    left: Box::new(Integer {
+                        value: 3,
+                        span: Span::default(),
+                    }),
+                    right: Box::new(Integer {
+                        value: 4,
+                        span: Span::default(),
+                    }),
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            }),
+            right: Box::new(NotEqual {
+                left: Box::new(Integer {
+                    value: 5,
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 6,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            }),
+            span: Span::default(),
+        });
+
+        assert_eq!(format!("{}", expression), format!("{}", expected));
+
+        let expression = parse_expression("a == b != !c");
+        assert_eq!(format!("{}", expression), "NotEqual(Equal(a, b), Not(c))");
+    }
+
+    #[test]
+    fn test_functions() {
+        let program = parse_program("fn add(a: i32, b: i32) -> i32 { return a + b; }");
+        assert_eq!(
+            format!("{}", program),
+            "Program(Function(add, [a, b], Block(Return(Addition(a, b)))))"
+        );
+
+        let program = parse_program("fn zero() -> i32 { return 0; }");
+        assert_eq!(
+            format!("{}", program),
+            "Program(Function(zero, [], Block(Return(0))))"
+        );
+
+        let call = parse_expression("f(1, g(x) * 2, h()) + f");
+        assert_eq!(
+            format!("{}", call),
+            "Addition(Call(f, [1, Multiplication(Call(g, [x]), 2), Call(h, [])]), f)"
+        );
+
+        let block = parse("{ f(); g(1) }");
+        assert_eq!(format!("{}", block), "Block(Call(f, []), Call(g, [1]))");
+
+        let tokens = Lexer::new("test", "{ f() return 0; }").tokenize();
+        Instruction::parse(tokens.stream()).unwrap();
+        let diagnostics = tokens.into_diagnostics();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test:1:7: failed to parse expression statement, expected ';'"
+        );
+
+        let tokens = Lexer::new("test", "fn f(a: i32,) -> i32 {} fn g() { return 1; }").tokenize();
+        super::parse(tokens.stream());
+        let diagnostics = tokens
+            .into_diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                "test:1:13: failed to parse identifier, expected identifier",
+                "test:1:32: failed to parse function, expected '->'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyword_prefixes() {
+        let expression = parse("let lenght: i32 = iffy + whiles;");
+
+        let expected = Box::new(Declaration {
+            left: Identifier {
+                name: "lenght".to_owned(),
+                span: Span::default(),
+            },
+            right: Box::new(Addition {
+                left: Box::new(Identifier {
+                    name: "iffy".to_owned(),
+                    span: Span::default(),
+                }),
+                right: Box::new(Identifier {
+                    name: "whiles".to_owned(),
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            }),
+            span: Span::default(),
+        });
+
+        assert_eq!(format!("{}", expression), format!("{}", expected));
+
+        let expression = parse("if iffy { let elsewhere: i32; } else { whiles }");
+        assert_eq!(
+            format!("{}", expression),
+            "If(iffy, Block(Declaration(elsewhere, 0)), Block(whiles))"
+        );
+    }
+
+    #[test]
+    fn test_assignment() {
+        let expression = parse("x = x + 1;");
+
+        let expected = Box::new(Assignment {
+            left: Identifier {
+                name: "x".to_owned(),
+                span: Span::default(),
+            },
+            right: Box::new(Addition {
+                left: Box::new(Identifier {
+                    name: "x".to_owned(),
+                    span: Span::default(),
+                }),
+                right: Box::new(Integer {
+                    value: 1,
+                    span: Span::default(),
+                }),
+                span: Span::default(),
             }),
+            span: Span::default(),
         });
 
         assert_eq!(format!("{}", expression), format!("{}", expected));
+
+        let expression = parse("{ let x: i32 = 1; x = 2; x }");
+        assert_eq!(
+            format!("{}", expression),
+            "Block(Declaration(x, 1), Assignment(x, 2), x)"
+        );
+
+        let expression = parse("{ x += 1; x -= 2 * y; x *= 3; x /= 4; x %= 5; }");
+        assert_eq!(
+            format!("{}", expression),
+            "Block(CompoundAssignment(x, +=, 1), \
+             CompoundAssignment(x, -=, Multiplication(2, y)), \
+             CompoundAssignment(x, *=, 3), \
+             CompoundAssignment(x, /=, 4), \
+             CompoundAssignment(x, %=, 5))"
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let expression = parse("let x: i32 = 1 +\n    2;");
+        let span = expression.span();
+        assert_eq!((span.start.line, span.start.column), (1, 1));
+        assert_eq!((span.end.line, span.end.column), (2, 7));
+
+        let block = parse("{\n    1\n}");
+        assert_eq!(block.span().last_character().to_string(), "test:3:1");
+
+        let tokens = Lexer::new("test", "{\n    let x: i32 = 5\n}").tokenize();
+        Instruction::parse(tokens.stream()).unwrap();
+        let diagnostics = tokens.into_diagnostics();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test:3:1: failed to parse declaration, expected ';'"
+        );
+    }
+
+    #[test]
+    fn test_break_continue() {
+        let expression = parse("while x { if x { break; } continue; }");
+        assert_eq!(
+            format!("{}", expression),
+            "While(x, Block(If(x, Block(Break)), Continue))"
+        );
+
+        let source = "fn main() -> i32 { while 1 { } break; if 1 { continue; } return 0; }";
+        let tokens = Lexer::new("test", source).tokenize();
+        super::parse(tokens.stream());
+        let diagnostics = tokens
+            .into_diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                "test:1:32: 'break' is only allowed inside a loop",
+                "test:1:46: 'continue' is only allowed inside a loop",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_for() {
+        let expression = parse("for i in 0..n + 1 { break; }");
+        assert_eq!(
+            format!("{}", expression),
+            "For(i, Range(0, Addition(n, 1)), Block(Break))"
+        );
+
+        let expression = parse("for i in -3..=f(2) { }");
+        assert_eq!(
+            format!("{}", expression),
+            "For(i, RangeInclusive(-3, Call(f, [2])), Block())"
+        );
+
+        let tokens = Lexer::new("test", "for i in 10 { }").tokenize();
+        assert_eq!(
+            Instruction::parse(tokens.stream())
+                .err()
+                .unwrap()
+                .to_string(),
+            "test:1:13: failed to parse range, expected '..' or '..='"
+        );
+    }
+
+    #[test]
+    fn test_program() {
+        let program = parse_program(
+            "const N: i32 = 2 * 3; let total: i32; fn main() -> i32 { return N + total; }",
+        );
+        assert_eq!(
+            format!("{}", program),
+            "Program(Constant(N, Multiplication(2, 3)), Global(total, 0), \
+             Function(main, [], Block(Return(Addition(N, total)))))"
+        );
+
+        let program = parse_program("// nothing but a comment");
+        assert_eq!(format!("{}", program), "Program()");
+
+        let tokens = Lexer::new("test", "fn main() -> i32 { return 0; } 42").tokenize();
+        super::parse(tokens.stream());
+        let diagnostics = tokens.into_diagnostics();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test:1:32: failed to parse item, expected 'fn', 'let' or 'const'"
+        );
+
+        let tokens = Lexer::new("test", "const N: i32;").tokenize();
+        super::parse(tokens.stream());
+        let diagnostics = tokens.into_diagnostics();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test:1:13: failed to parse constant, expected '='"
+        );
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let source = "\
+let a: i32 = ;
+fn f() -> i32 {
+    if a {
+        let b: i32 = 1
+    } else {
+        while { }
+    }
+    let c: i32 = (a + 1;
+    return c;
+}
+}
+const d: i32 = a * 2;
+d + 1;
+";
+        let tokens = Lexer::new("test", source).tokenize();
+        let program = super::parse(tokens.stream());
+        assert_eq!(
+            format!("{}", program),
+            "Program(Function(f, [], Block(If(a, Block(), Block()), Return(c))), \
+             Constant(d, Multiplication(a, 2)))"
+        );
+
+        let diagnostics = tokens
+            .into_diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                "test:1:14: failed to parse identifier, expected identifier",
+                "test:5:5: failed to parse declaration, expected ';'",
+                "test:6:15: failed to parse identifier, expected identifier",
+                "test:8:24: failed to parse expression, expected ')'",
+                "test:11:1: unexpected '}'",
+                "test:13:1: failed to parse item, expected 'fn', 'let' or 'const'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let expression = parse_expression("1 + /* two */ 2 // trailing\n * 3");
+        assert_eq!(
+            format!("{}", expression),
+            "Addition(1, Multiplication(2, 3))"
+        );
+
+        let expression = parse(
+            "{
+                // leading
+                let x: i32 = 1; /* between */ let y: i32 = 2;
+                /* nested /* comment */ x */
+            }",
+        );
+        assert_eq!(
+            format!("{}", expression),
+            "Block(Declaration(x, 1), Declaration(y, 2))"
+        );
+
+        let expression = parse("if x { 1 } // then\n/* otherwise */ else { 2 }");
+        assert_eq!(format!("{}", expression), "If(x, Block(1), Block(2))");
+    }
+
+    fn time_parse(statements: usize) -> Duration {
+        let source = format!(
+            "{{\n{}}}",
+            "    let x: i32 = (x + 1) * 2 < 3 && !y;\n".repeat(statements)
+        );
+
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                parse(&source);
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Timing-based, so it is only run on request with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn bench_parse_is_linear() {
+        let small = time_parse(5_000);
+        let large = time_parse(40_000);
+
+        // 8x the input must stay well below the 64x a quadratic parser needs.
+        let ratio = large.as_secs_f64() / small.as_secs_f64();
+        assert!(ratio < 24.0, "parsing looks superlinear: ratio {ratio:.1}");
     }
 }
@@ -0,0 +1,88 @@
+use crate::{
+    ast::{
+        AstNode,
+        program::{Constant, Item, Program as AstProgram},
+    },
+    diagnostics::{Diagnostic, Result},
+    lexer::{TokenKind, TokenStream},
+    parser::{
+        Declaration, Function, Or, Parser, Program,
+        utils::{Type, parse_identifier, parse_type, synchronize},
+    },
+};
+
+impl Parser for Program {
+    type TNext = Function;
+
+    fn parse(tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        let (program, tokens) = Self::parse_unboxed(tokens);
+
+        Ok((Box::new(program), tokens))
+    }
+}
+
+impl Program {
+    /// Parses items up to the end of the input, recovering like `parse_instructions`.
+    pub fn parse_unboxed(mut tokens: TokenStream<'_>) -> (AstProgram, TokenStream<'_>) {
+        let start = tokens.span();
+        let mut items = Vec::new();
+
+        while tokens.peek_kind().is_some() {
+            match parse_item(tokens) {
+                Ok((item, remainder)) => {
+                    items.push(item);
+                    tokens = remainder;
+                }
+                Err(diagnostic) => {
+                    tokens.report(diagnostic);
+                    tokens = synchronize(tokens);
+                    // `synchronize` stops before a `}` it did not open, which at
+                    // the top level has nothing to close.
+                    if tokens.peek_kind() == Some(TokenKind::RightBrace) {
+                        tokens.next_token();
+                    }
+                }
+            }
+        }
+
+        let span = start.to(&tokens.span());
+        (AstProgram { items, span }, tokens)
+    }
+}
+
+fn parse_item(tokens: TokenStream<'_>) -> Result<(Item, TokenStream<'_>)> {
+    match tokens.peek_kind() {
+        Some(TokenKind::Fn) => {
+            let (function, tokens) = Function::parse_unboxed(tokens)?;
+            Ok((Item::Function(function), tokens))
+        }
+        Some(TokenKind::Let) => {
+            let (global, tokens) = Declaration::parse_unboxed(tokens)?;
+            Ok((Item::Global(global), tokens))
+        }
+        Some(TokenKind::Const) => {
+            let (constant, tokens) = parse_constant(tokens)?;
+            Ok((Item::Constant(constant), tokens))
+        }
+        Some(TokenKind::RightBrace) => Err(Diagnostic::new("unexpected '}'", tokens.span())),
+        _ => Err(Diagnostic::new(
+            "failed to parse item, expected 'fn', 'let' or 'const'",
+            tokens.span(),
+        )),
+    }
+}
+
+fn parse_constant(mut tokens: TokenStream<'_>) -> Result<(Constant, TokenStream<'_>)> {
+    let keyword = tokens.expect(TokenKind::Const, "constant")?;
+
+    let (name, mut tokens) = parse_identifier(tokens)?;
+    tokens.expect(TokenKind::Colon, "constant")?;
+    let (Type::I32, mut tokens) = parse_type(tokens)?;
+    tokens.expect(TokenKind::Assign, "constant")?;
+
+    let (value, mut tokens) = Or::parse(tokens)?;
+    let semicolon = tokens.expect(TokenKind::Semicolon, "constant")?;
+
+    let span = keyword.span.to(&semicolon.span);
+    Ok((Constant { name, value, span }, tokens))
+}
@@ -1,40 +1,34 @@
 use crate::{
-    ast::{AstNode, rvalues::*},
-    parser::{Factor, Identifier, Instruction, Parser},
-    peek::StringUtils,
+    ast::AstNode,
+    diagnostics::Result,
+    lexer::{TokenKind, TokenStream},
+    parser::{Call, Factor, Identifier, Or, Parser, utils::parse_integer},
 };
-use anyhow::{Result, anyhow};
 
 impl Parser for Factor {
-    type TNext = Instruction;
+    type TNext = Or;
 
-    fn parse(string: String) -> Result<(Box<dyn AstNode>, String)> {
-        let mut string = string.trim_mut();
-        if string.peek().is_some_and(|c| c == '(') {
-            string.remove(0);
-            let (left, remainder) = Self::TNext::parse(string)?;
+    fn parse(mut tokens: TokenStream<'_>) -> Result<(Box<dyn AstNode>, TokenStream<'_>)> {
+        match tokens.peek_kind() {
+            Some(TokenKind::LeftParen) => {
+                tokens.next_token();
+                let (left, mut tokens) = Self::TNext::parse(tokens)?;
+                tokens.expect(TokenKind::RightParen, "expression")?;
 
-            string = remainder.trim_mut();
-            if string.peek().is_some_and(|c| c == ')') {
-                string.remove(0);
-
-                return Ok((left, string));
+                Ok((left, tokens))
             }
+            Some(TokenKind::Integer) => {
+                let token = tokens.next_token().unwrap();
+                let integer = parse_integer(token.text, token.span.clone())?;
 
-            return Err(anyhow!("failed to parse expression, expected ')'"));
-        } else if string.peek().is_some_and(|c| c.is_numeric()) {
-            let mut value_str = String::default();
-            while string.peek().is_some_and(|c| c.is_digit(10)) {
-                value_str.push(string.remove(0));
+                Ok((integer, tokens))
             }
-
-            if let Ok(value) = value_str.parse::<i32>() {
-                return Ok((Box::new(Integer { value }), string));
-            } else {
-                Err(anyhow!("failed to parse integer"))
+            Some(TokenKind::Identifier)
+                if tokens.peek_nth_kind(1) == Some(TokenKind::LeftParen) =>
+            {
+                Call::parse(tokens)
             }
-        } else {
-            Identifier::parse(string)
+            _ => Identifier::parse(tokens),
         }
     }
 }
@@ -1,43 +1,130 @@
-use crate::{ast::AstNode, parser::Parser, peek::StringUtils};
-use anyhow::Result;
+use crate::{
+    ast::{AstNode, lvalues::Identifier, rvalues::Integer},
+    diagnostics::{Diagnostic, Result},
+    lexer::{Token, TokenKind, TokenStream},
+    parser::{Instruction, ParseContext, Parser},
+    span::Span,
+};
 
 pub enum Type {
     I32,
 }
 
-pub fn parse_binary_op<T, TNext, F>(
-    string: String,
-    separators: &[char],
+pub fn parse_binary_op<'a, TNext, F>(
+    tokens: TokenStream<'a>,
+    operators: &[TokenKind],
     right_parse: F,
-) -> Result<(Box<dyn AstNode>, String)>
+) -> Result<(Box<dyn AstNode>, TokenStream<'a>)>
 where
     TNext: Parser,
-    F: Fn(Box<dyn AstNode>, String) -> Result<(Box<dyn AstNode>, String)>,
+    F: Fn(
+        Box<dyn AstNode>,
+        &'a Token<'a>,
+        TokenStream<'a>,
+    ) -> Result<(Box<dyn AstNode>, TokenStream<'a>)>,
 {
-    let (mut left, string) = TNext::parse(string.trim_mut())?;
-    let mut string = string.trim_mut();
+    let (mut left, mut tokens) = TNext::parse(tokens)?;
 
-    while string.peek().is_some_and(|c| separators.contains(&c)) {
-        let (parsed, remainder) = right_parse(left, string)?;
+    while tokens
+        .peek_kind()
+        .is_some_and(|kind| operators.contains(&kind))
+    {
+        let operator = tokens.next_token().unwrap();
+        let (parsed, remainder) = right_parse(left, operator, tokens)?;
         left = parsed;
-        string = remainder;
+        tokens = remainder;
     }
 
-    Ok((left, string))
+    Ok((left, tokens))
 }
 
-pub fn parse_type(mut string: String) -> Result<(Type, String)> {
-    if !string.peek().is_some_and(|c| c == 'i') {
-        return Err(anyhow::anyhow!("failed to parse type"));
+pub fn parse_integer(text: &str, span: Span) -> Result<Box<dyn AstNode>> {
+    match text.parse::<i32>() {
+        Ok(value) => Ok(Box::new(Integer { value, span })),
+        Err(_) => Err(Diagnostic::new("failed to parse integer", span)),
     }
-    string.remove(0);
-    if !string.peek().is_some_and(|c| c == '3') {
-        return Err(anyhow::anyhow!("failed to parse type"));
+}
+
+/// Parses a bare identifier for the places, like the target of a declaration
+/// or assignment, where nothing else may appear.
+pub fn parse_identifier(mut tokens: TokenStream<'_>) -> Result<(Identifier, TokenStream<'_>)> {
+    let token = tokens.expect(TokenKind::Identifier, "identifier")?;
+
+    Ok((
+        Identifier {
+            name: token.text.to_owned(),
+            span: token.span.clone(),
+        },
+        tokens,
+    ))
+}
+
+pub fn parse_type(mut tokens: TokenStream<'_>) -> Result<(Type, TokenStream<'_>)> {
+    let span = tokens.span();
+    match tokens.next_token() {
+        Some(token) if token.kind == TokenKind::Identifier && token.text == "i32" => {
+            Ok((Type::I32, tokens))
+        }
+        _ => Err(Diagnostic::new("failed to parse type", span)),
+    }
+}
+
+/// Parses instructions up to the `}` closing the current block or the end of
+/// the input. A malformed instruction is reported and skipped so the
+/// following ones are still checked.
+pub fn parse_instructions(
+    mut tokens: TokenStream<'_>,
+    context: ParseContext,
+) -> (Vec<Box<dyn AstNode>>, TokenStream<'_>) {
+    let mut instructions = Vec::new();
+
+    while tokens
+        .peek_kind()
+        .is_some_and(|kind| kind != TokenKind::RightBrace)
+    {
+        match Instruction::parse_in(tokens, context) {
+            Ok((instruction, remainder)) => {
+                instructions.push(instruction);
+                tokens = remainder;
+            }
+            Err(diagnostic) => {
+                tokens.report(diagnostic);
+                tokens = synchronize(tokens);
+            }
+        }
     }
-    string.remove(0);
-    if !string.peek().is_some_and(|c| c == '2') {
-        return Err(anyhow::anyhow!("failed to parse type"));
+
+    (instructions, tokens)
+}
+
+/// Skips the instruction starting at `tokens`: everything up to and including
+/// the next `;`, or a whole `{ ... }` group together with any `else` group
+/// following it. Stops before a `}` closing the enclosing block.
+pub fn synchronize(mut tokens: TokenStream<'_>) -> TokenStream<'_> {
+    let mut depth = 0usize;
+
+    while let Some(kind) = tokens.peek_kind() {
+        match kind {
+            TokenKind::Semicolon if depth == 0 => {
+                tokens.next_token();
+                break;
+            }
+            TokenKind::RightBrace if depth == 0 => break,
+            TokenKind::LeftBrace => depth += 1,
+            TokenKind::RightBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    tokens.next_token();
+                    if tokens.peek_kind() == Some(TokenKind::Else) {
+                        continue;
+                    }
+                    break;
+                }
+            }
+            _ => {}
+        }
+        tokens.next_token();
     }
-    string.remove(0);
-    Ok((Type::I32, string))
+
+    tokens
 }
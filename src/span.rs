@@ -0,0 +1,69 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    sync::Arc,
+};
+
+/// A location in a source file. `line` and `column` are 1-based, `column`
+/// counts characters rather than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+    pub file: Arc<str>,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Position {
+    pub fn new() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub fn advance(&mut self, c: char) {
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+}
+
+impl Span {
+    pub fn new(file: Arc<str>, start: Position, end: Position) -> Self {
+        Self { file, start, end }
+    }
+
+    /// Returns a span covering everything from the start of `self` to the end
+    /// of `other`.
+    pub fn to(&self, other: &Span) -> Span {
+        Span::new(self.file.clone(), self.start, other.end)
+    }
+
+    /// Returns the span of the last character of `self`, such as the `}`
+    /// closing a block. That character must be ASCII.
+    pub fn last_character(&self) -> Span {
+        let start = Position {
+            offset: self.end.offset - 1,
+            line: self.end.line,
+            column: self.end.column - 1,
+        };
+        Span::new(self.file.clone(), start, self.end)
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}:{}:{}", self.file, self.start.line, self.start.column)
+    }
+}